@@ -0,0 +1,56 @@
+use crate::config::quote;
+use crate::indices::config_value;
+use crate::raw_api::Result;
+use crate::Session;
+
+impl Session {
+    /// Replaces the application metadata stored with `uri` (the `app_metadata`
+    /// it was created with), which WiredTiger keeps but doesn't interpret.
+    pub fn set_app_metadata(&self, uri: &str, metadata: &str) -> Result<()> {
+        self.alter(uri, &format!("app_metadata={}", quote(metadata)))
+    }
+
+    /// The application metadata stored with `uri`, empty if there's none.
+    pub fn get_app_metadata(&self, uri: &str) -> Result<String> {
+        let metadata = self.open_cursor("metadata:")?;
+        metadata.set_key(uri)?;
+        metadata.search()?;
+        let (_, config) = metadata.get_raw_key_value()?;
+        let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+        Ok(config_value(&config, "app_metadata")
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Connection, CreateConfig};
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_app_metadata() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let config = CreateConfig::builder()
+            .key_format("S")
+            .value_format("S")
+            .build();
+        assert_ok!(sess.create_with("table:annotated", &config));
+        assert_eq!(assert_ok!(sess.get_app_metadata("table:annotated")), "");
+
+        assert_ok!(sess.set_app_metadata("table:annotated", "owner=billing,version=2"));
+        assert_eq!(
+            assert_ok!(sess.get_app_metadata("table:annotated")),
+            "owner=billing,version=2"
+        );
+        assert!(sess
+            .get_app_metadata("table:missing")
+            .unwrap_err()
+            .is_not_found());
+    }
+}
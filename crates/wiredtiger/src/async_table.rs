@@ -0,0 +1,179 @@
+use crate::raw_api::{Error, Result};
+use crate::{Connection, Cursor};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce(&Cursor<'_>) + Send>;
+
+/// Reads and writes a table from async code. Each operation runs on one of a
+/// few OS threads, each with its own session and cursor, so the executor never
+/// blocks on WiredTiger. Keys and values are packed, as with a raw cursor; for
+/// tables whose key and value formats are `u` that's just the bytes.
+pub struct AsyncTable {
+    jobs: Option<Mutex<Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl AsyncTable {
+    /// Starts `threads` worker threads (at least one) on `uri`, failing if any
+    /// of them can't open a session or cursor.
+    pub fn open(conn: &Connection, uri: &str, threads: usize) -> Result<Self> {
+        let (jobs, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let (ready, opened) = mpsc::channel();
+        let mut table = AsyncTable {
+            jobs: Some(Mutex::new(jobs)),
+            workers: Vec::new(),
+        };
+        let threads = threads.max(1);
+        for _ in 0..threads {
+            let (conn, uri, receiver, ready) = (
+                conn.clone(),
+                uri.to_string(),
+                receiver.clone(),
+                ready.clone(),
+            );
+            table.workers.push(std::thread::spawn(move || {
+                work(conn, &uri, &receiver, ready)
+            }));
+        }
+        drop(ready);
+        // Dropping the table on failure stops the threads that did start.
+        for result in opened.iter().take(threads) {
+            result?;
+        }
+        Ok(table)
+    }
+
+    /// Looks up `key`, returning its value or `None` if it isn't there.
+    pub async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let key = key.to_vec();
+        self.run(move |cursor| {
+            cursor.raw_cursor.set_key_item(&key)?;
+            let value = match cursor.search() {
+                Ok(()) => Some(cursor.raw_cursor.get_packed_key_value()?.1),
+                Err(err) if err.is_not_found() => None,
+                Err(err) => return Err(err),
+            };
+            cursor.reset()?;
+            Ok(value)
+        })
+        .await
+    }
+
+    /// Sets `key` to `value`, overwriting any value it had.
+    pub async fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        let (key, value) = (key.to_vec(), value.to_vec());
+        self.run(move |cursor| {
+            cursor.raw_cursor.set_key_item(&key)?;
+            cursor.raw_cursor.set_value_item(&value)?;
+            cursor.raw_cursor.insert()
+        })
+        .await
+    }
+
+    /// Removes `key`. Removing a key that isn't there isn't an error.
+    pub async fn remove(&self, key: &[u8]) -> Result<()> {
+        let key = key.to_vec();
+        self.run(move |cursor| {
+            cursor.raw_cursor.set_key_item(&key)?;
+            cursor.remove()
+        })
+        .await
+    }
+
+    async fn run<T: Send + 'static, F: FnOnce(&Cursor<'_>) -> Result<T> + Send + 'static>(
+        &self,
+        f: F,
+    ) -> Result<T> {
+        let (done, result) = oneshot::channel();
+        let job: Job = Box::new(move |cursor| {
+            // The caller may have stopped waiting.
+            let _ = done.send(f(cursor));
+        });
+        let stopped = || Error::new("AsyncTable's worker threads have stopped");
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .send(job)
+            .map_err(|_| stopped())?;
+        result.await.map_err(|_| stopped())?
+    }
+}
+
+impl Drop for AsyncTable {
+    fn drop(&mut self) {
+        // Closing the channel lets the workers finish the jobs already sent and
+        // then exit, closing their sessions.
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work(conn: Connection, uri: &str, jobs: &Mutex<Receiver<Job>>, ready: Sender<Result<()>>) {
+    let session = match conn.open_session() {
+        Ok(session) => session,
+        Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+        }
+    };
+    let cursor = match session.open_cursor_with_config(uri, "raw") {
+        Ok(cursor) => cursor,
+        Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+    loop {
+        // The lock is only held while waiting for a job, not while running it.
+        let job = jobs.lock().unwrap().recv();
+        match job {
+            Ok(job) => job(&cursor),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncTable;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[tokio::test]
+    async fn test_async_table() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:async", "key_format=u,value_format=u"));
+        assert!(AsyncTable::open(&conn, "table:missing", 2).is_err());
+
+        let table = assert_ok!(AsyncTable::open(&conn, "table:async", 2));
+        for i in 0..10u8 {
+            assert_ok!(table.put(&[i], &[i * 2]).await);
+        }
+        assert_eq!(assert_ok!(table.get(&[3]).await), Some(vec![6]));
+        assert_ok!(table.remove(&[3]).await);
+        assert_eq!(assert_ok!(table.get(&[3]).await), None);
+        drop(table);
+
+        // Everything was written through the worker threads' sessions.
+        let cur = assert_ok!(sess.open_cursor("table:async"));
+        let mut rows = 0;
+        while cur.next().is_ok() {
+            rows += 1;
+        }
+        assert_eq!(rows, 9);
+    }
+}
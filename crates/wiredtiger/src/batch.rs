@@ -0,0 +1,106 @@
+use crate::raw_api::{Error, Result};
+use crate::Cursor;
+
+impl<'a> Cursor<'a> {
+    /// Inserts each `(key, value)` pair in turn, returning how many were
+    /// inserted. The cursor must be raw, or its key and value formats `u`. It
+    /// stops at the first failure, whose error message says which pair failed
+    /// and whose code is WiredTiger's; the pairs before it stay inserted unless
+    /// the batch runs inside a transaction (e.g. `Session::with_transaction`),
+    /// which also makes large batches cheaper to commit.
+    pub fn batch_insert(&self, pairs: &[(&[u8], &[u8])]) -> Result<usize> {
+        for (i, (key, value)) in pairs.iter().enumerate() {
+            let result = self
+                .raw_cursor
+                .set_key_item(key)
+                .and_then(|()| self.raw_cursor.set_value_item(value))
+                .and_then(|()| self.raw_cursor.insert());
+            if let Err(err) = result {
+                return Err(Error {
+                    message: format!("batch insert failed at pair {}: {}", i, err.message),
+                    ..err
+                });
+            }
+        }
+        Ok(pairs.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+    use std::time::Instant;
+    use wiredtiger_sys as wtffi;
+
+    #[test]
+    fn test_batch_insert() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:batch", "key_format=u,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor_with_config("table:batch", "overwrite=false"));
+
+        let pairs: [(&[u8], &[u8]); 2] = [(b"a", b"1"), (b"b", b"2")];
+        assert_eq!(assert_ok!(cur.batch_insert(&pairs)), 2);
+
+        // Without overwrite, inserting "b" again fails part way through.
+        let pairs: [(&[u8], &[u8]); 3] = [(b"c", b"3"), (b"b", b"4"), (b"d", b"5")];
+        let err = cur.batch_insert(&pairs).unwrap_err();
+        assert_eq!(err.code, wtffi::WT_DUPLICATE_KEY);
+        assert!(err.message.starts_with("batch insert failed at pair 1: "));
+        let mut count = 0;
+        assert_ok!(cur.reset());
+        while cur.next().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    // A rough benchmark, run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_batch_insert() {
+        const ROWS: usize = 50_000;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let keys: Vec<Vec<u8>> = (0..ROWS)
+            .map(|i| format!("{:08}", i).into_bytes())
+            .collect();
+        let pairs: Vec<(&[u8], &[u8])> = keys.iter().map(|k| (&k[..], &b"value"[..])).collect();
+
+        assert_ok!(sess.create("table:single", "key_format=u,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:single"));
+        let start = Instant::now();
+        for (key, value) in &pairs {
+            assert_ok!(cur.raw_cursor.set_key_item(key));
+            assert_ok!(cur.raw_cursor.set_value_item(value));
+            assert_ok!(cur.insert());
+        }
+        let single = start.elapsed();
+
+        assert_ok!(sess.create("table:batched", "key_format=u,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:batched"));
+        let start = Instant::now();
+        assert_eq!(assert_ok!(cur.batch_insert(&pairs)), ROWS);
+        let batched = start.elapsed();
+
+        assert_ok!(sess.create("table:transaction", "key_format=u,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:transaction"));
+        let start = Instant::now();
+        assert_ok!(sess.with_transaction("", |_| cur.batch_insert(&pairs)));
+        let in_transaction = start.elapsed();
+
+        println!(
+            "{} rows: {:?} one at a time, {:?} batched, {:?} batched in a transaction",
+            ROWS, single, batched, in_transaction
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::raw_api::Result;
+use crate::Cursor;
+use wiredtiger_sys as wtffi;
+
+// Losing a write conflict to another transaction means the swap didn't happen.
+fn swapped(result: Result<()>) -> Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(err) if err.code == wtffi::WT_ROLLBACK => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+impl<'a> Cursor<'a> {
+    /// Sets `key` to `new` if its current value is `expected`, or if it doesn't
+    /// exist when `expected` is `None`, returning whether the value was written.
+    /// The check and the write happen in a transaction of their own, so this
+    /// mustn't be called inside one, and a conflicting write from another
+    /// transaction also counts as a mismatch. The cursor's value format must be
+    /// `u`.
+    pub fn compare_and_swap(&self, key: &str, expected: Option<&[u8]>, new: &[u8]) -> Result<bool> {
+        let session = &self.session.raw_session;
+        session.begin_transaction("")?;
+        let result = match self.swap_if(key, expected, new) {
+            Ok(true) => return swapped(session.commit_transaction("")),
+            Ok(false) => Ok(false),
+            Err(err) => swapped(Err(err)),
+        };
+        session.rollback_transaction("")?;
+        result
+    }
+
+    fn swap_if(&self, key: &str, expected: Option<&[u8]>, new: &[u8]) -> Result<bool> {
+        self.set_key(key)?;
+        let current = match self.search() {
+            // A `u` value is packed as its bytes alone.
+            Ok(()) => Some(self.raw_cursor.get_packed_key_value()?.1),
+            Err(err) if err.is_not_found() => None,
+            Err(err) => return Err(err),
+        };
+        if current.as_deref() != expected {
+            return Ok(false);
+        }
+        self.set_key(key)?;
+        self.raw_cursor.set_value_item(new)?;
+        self.insert()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_compare_and_swap() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:cas", "key_format=S,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:cas"));
+
+        assert!(assert_ok!(cur.compare_and_swap("k", None, b"v0")));
+        assert!(!assert_ok!(cur.compare_and_swap("k", None, b"v1")));
+        assert!(!assert_ok!(cur.compare_and_swap("k", Some(b"v1"), b"v2")));
+        assert!(assert_ok!(cur.compare_and_swap("k", Some(b"v0"), b"v1")));
+    }
+
+    #[test]
+    fn test_compare_and_swap_race() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let first = assert_ok!(conn.open_session());
+        let second = assert_ok!(conn.open_session());
+        assert_ok!(first.create("table:cas", "key_format=S,value_format=u"));
+        let first_cur = assert_ok!(first.open_cursor("table:cas"));
+        let second_cur = assert_ok!(second.open_cursor("table:cas"));
+        assert!(assert_ok!(first_cur.compare_and_swap("k", None, b"v0")));
+
+        // Both sessions see "v0", but the first's swap is mid-transaction when the
+        // second tries, so the second loses.
+        assert_ok!(first.begin_transaction(""));
+        assert!(assert_ok!(first_cur.swap_if("k", Some(b"v0"), b"first")));
+        assert!(!assert_ok!(second_cur.compare_and_swap(
+            "k",
+            Some(b"v0"),
+            b"second"
+        )));
+        assert_ok!(first.commit_transaction(""));
+
+        assert_ok!(second_cur.set_key("k"));
+        assert_ok!(second_cur.search());
+        assert_eq!(
+            assert_ok!(second_cur.raw_cursor.get_packed_key_value()).1,
+            b"first"
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use crate::raw_api::{Error, Result};
+use crate::{checkpoint_config, CheckpointConfig, Connection, Cursor, Session};
+use std::cmp::Ordering;
+use std::time::{Duration, SystemTime};
+
+/// Details of the most recent checkpoint, for coordinating backups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointInfo {
+    /// The stable timestamp the checkpoint was taken at, if timestamps are in use.
+    pub timestamp: Option<u64>,
+    /// The log position (file number, offset) the checkpoint covers.
+    pub lsn: (u32, u32),
+    /// When the checkpoint was taken, to a one-second resolution.
+    pub time: SystemTime,
+}
+
+// A file's metadata lists each of its checkpoints as
+// `name=(addr=...,order=N,time=SECONDS,...)`.
+fn checkpoint_times(config: &str) -> impl Iterator<Item = u64> + '_ {
+    config.match_indices(",time=").filter_map(|(i, key)| {
+        let rest = &config[i + key.len()..];
+        rest[..rest.find([',', ')']).unwrap_or(rest.len())]
+            .parse()
+            .ok()
+    })
+}
+
+fn parse_lsn(config: &str) -> Option<(u32, u32)> {
+    let start = config.find("checkpoint_lsn=(")? + "checkpoint_lsn=(".len();
+    let rest = &config[start..];
+    let (file, offset) = rest[..rest.find(')')?].split_once(',')?;
+    Some((file.parse().ok()?, offset.parse().ok()?))
+}
+
+impl Connection {
+    /// Like `open`, but with the periodic checkpoints set out by `checkpoint`
+    /// added to `options`.
+    pub fn open_with_checkpoint(
+        filename: &str,
+        options: &str,
+        checkpoint: &CheckpointConfig,
+    ) -> Result<Self> {
+        let checkpoint = checkpoint.to_string();
+        let options = match (options.is_empty(), checkpoint.is_empty()) {
+            (_, true) => options.to_string(),
+            (true, false) => checkpoint,
+            (false, false) => format!("{},{}", options, checkpoint),
+        };
+        Self::open(filename, &options)
+    }
+
+    /// Reports the timestamp, log position and time of the most recent checkpoint,
+    /// gathered from the per-file checkpoint records in the metadata.
+    pub fn last_checkpoint_info(&self) -> Result<CheckpointInfo> {
+        let session = self.open_session()?;
+        let cursor = session.open_cursor("metadata:")?;
+        let mut latest: Option<(u64, (u32, u32))> = None;
+        loop {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+            let (uri, config) = cursor.get_raw_key_value()?;
+            if !uri.unwrap_or_default().starts_with(b"file:") {
+                continue;
+            }
+            let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+            let Some(time) = checkpoint_times(&config).max() else {
+                continue;
+            };
+            let lsn = parse_lsn(&config).unwrap_or_default();
+            if latest.is_none_or(|(t, l)| (time, lsn) > (t, l)) {
+                latest = Some((time, lsn));
+            }
+        }
+        let (time, lsn) = latest.ok_or_else(|| Error::new("no checkpoint has been taken"))?;
+
+        let timestamp = self.raw_conn().query_timestamp("get=last_checkpoint")?.0;
+        Ok(CheckpointInfo {
+            timestamp: (timestamp != 0).then_some(timestamp),
+            lsn,
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(time),
+        })
+    }
+}
+
+impl Session {
+    /// Reports every record of `uri` that was added or changed since the named
+    /// checkpoint, by walking the table and the checkpoint side by side. Keys and
+    /// values are passed to `cb` packed, as WiredTiger stores them. Removed
+    /// records aren't reported. Returns the number of records reported.
+    ///
+    /// This works without logging, but reads the whole table, and it assumes the
+    /// table uses the default collator (i.e. keys sort by their packed bytes).
+    pub fn changes_since<F: Fn(&[u8], &[u8])>(
+        &self,
+        uri: &str,
+        since_checkpoint: &str,
+        cb: F,
+    ) -> Result<u64> {
+        let current = self.open_cursor_with_config(uri, "raw")?;
+        let checkpoint = self.open_cursor_with_config(
+            uri,
+            &format!("{},raw", checkpoint_config(since_checkpoint)?),
+        )?;
+        let mut count = 0;
+        merge_scan(&checkpoint, &current, |key, old, new| {
+            if let Some(new) = new {
+                if old != Some(new) {
+                    cb(key, new);
+                    count += 1;
+                }
+            }
+        })?;
+        Ok(count)
+    }
+
+    /// Lists every record of `uri` that was added, modified or removed between
+    /// the named checkpoints `from` and `to`, in key order, by walking the two
+    /// checkpoints side by side. Keys and values are packed, and as with
+    /// `changes_since` the table must use the default collator.
+    pub fn checkpoint_delta(&self, uri: &str, from: &str, to: &str) -> Result<Vec<Change>> {
+        let open = |checkpoint| {
+            self.open_cursor_with_config(uri, &format!("{},raw", checkpoint_config(checkpoint)?))
+        };
+        let (old, new) = (open(from)?, open(to)?);
+        let mut changes = Vec::new();
+        merge_scan(&old, &new, |key, old, new| {
+            let key = key.to_vec();
+            changes.extend(match (old, new) {
+                (None, Some(value)) => Some(Change::Added {
+                    key,
+                    value: value.to_vec(),
+                }),
+                (Some(value), None) => Some(Change::Removed {
+                    key,
+                    value: value.to_vec(),
+                }),
+                (Some(old), Some(new)) if old != new => Some(Change::Modified {
+                    key,
+                    old_value: old.to_vec(),
+                    new_value: new.to_vec(),
+                }),
+                _ => None,
+            });
+        })?;
+        Ok(changes)
+    }
+}
+
+/// A record that differs between two checkpoints, as reported by
+/// `Session::checkpoint_delta`. Keys and values are packed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Modified {
+        key: Vec<u8>,
+        old_value: Vec<u8>,
+        new_value: Vec<u8>,
+    },
+    Removed {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+}
+
+// Walks two raw cursors on versions of the same table side by side, calling `f`
+// with each key and its value in `old` and in `new`, or `None` where it's
+// missing. Keys are compared by their packed bytes.
+fn merge_scan<F: FnMut(&[u8], Option<&[u8]>, Option<&[u8]>)>(
+    old: &Cursor<'_>,
+    new: &Cursor<'_>,
+    mut f: F,
+) -> Result<()> {
+    // Steps a cursor, returning its packed record or None at the end.
+    let advance = |cursor: &Cursor<'_>| -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        match cursor.next() {
+            Ok(()) => cursor.raw_cursor.get_packed_key_value().map(Some),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    let (mut old_record, mut new_record) = (advance(old)?, advance(new)?);
+    loop {
+        let order = match (&old_record, &new_record) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_key, _)), Some((new_key, _))) => old_key.cmp(new_key),
+        };
+        match order {
+            Ordering::Less => {
+                let (key, value) = old_record.take().unwrap();
+                f(&key, Some(&value), None);
+                old_record = advance(old)?;
+            }
+            Ordering::Greater => {
+                let (key, value) = new_record.take().unwrap();
+                f(&key, None, Some(&value));
+                new_record = advance(new)?;
+            }
+            Ordering::Equal => {
+                let (key, old_value) = old_record.take().unwrap();
+                let (_, new_value) = new_record.take().unwrap();
+                f(&key, Some(&old_value), Some(&new_value));
+                old_record = advance(old)?;
+                new_record = advance(new)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checkpoint_times, Change};
+    use crate::{CheckpointConfig, Connection};
+    use assert_ok::assert_ok;
+    use std::cell::RefCell;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_open_with_checkpoint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let checkpoint = CheckpointConfig {
+            wait: 1,
+            ..Default::default()
+        };
+        let conn = assert_ok!(Connection::open_with_checkpoint(
+            temp_dir.path().to_str().unwrap(),
+            "create",
+            &checkpoint
+        ));
+        assert_eq!(conn.config(), "create,checkpoint=(wait=1)");
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:periodic", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:periodic"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("v"));
+        assert_ok!(cur.insert());
+        drop(cur);
+
+        // Nothing checkpoints the table but the checkpoint server.
+        std::thread::sleep(Duration::from_secs(3));
+        let files = assert_ok!(sess.list_by_type("file:periodic.wt"));
+        let (_, config) = files.first().expect("expected the table's file");
+        assert!(checkpoint_times(config).next().is_some());
+    }
+
+    #[test]
+    fn test_last_checkpoint_info() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create,log=(enabled)"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:ckpt", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:ckpt"));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        }
+
+        assert_ok!(conn.set_timestamp("stable_timestamp=2a"));
+        assert_ok!(sess.checkpoint(""));
+
+        let info = assert_ok!(conn.last_checkpoint_info());
+        assert_eq!(info.timestamp, Some(0x2a));
+        assert!(info.lsn.0 >= 1);
+        let age = SystemTime::now()
+            .duration_since(info.time)
+            .unwrap_or_default();
+        assert!(age < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:cdc", "key_format=S,value_format=S"));
+        let put = |k: &str, v: &str| {
+            let cur = assert_ok!(sess.open_cursor("table:cdc"));
+            assert_ok!(cur.set_key(k));
+            assert_ok!(cur.set_value(v));
+            assert_ok!(cur.insert());
+        };
+
+        put("a", "1");
+        put("b", "2");
+        put("c", "3");
+        assert_ok!(sess.checkpoint("name=before"));
+
+        put("b", "20"); // changed
+        put("d", "4"); // added
+        {
+            let cur = assert_ok!(sess.open_cursor("table:cdc"));
+            assert_ok!(cur.set_key("a"));
+            assert_ok!(cur.remove()); // removed, not reported
+        }
+
+        let changes = RefCell::new(Vec::new());
+        let count = assert_ok!(sess.changes_since("table:cdc", "before", |k, v| {
+            changes.borrow_mut().push((k.to_vec(), v.to_vec()));
+        }));
+        assert_eq!(count, 2);
+        assert_eq!(
+            changes.into_inner(),
+            [
+                (b"b\0".to_vec(), b"20\0".to_vec()),
+                (b"d\0".to_vec(), b"4\0".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_delta() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:delta", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:delta"));
+        let put = |k: &str, v: &str| {
+            assert_ok!(cur.set_key(k));
+            assert_ok!(cur.set_value(v));
+            assert_ok!(cur.insert());
+        };
+
+        put("a", "1");
+        put("b", "2");
+        put("c", "3");
+        assert_ok!(sess.checkpoint("name=from"));
+        put("b", "20");
+        put("d", "4");
+        assert_ok!(cur.set_key("c"));
+        assert_ok!(cur.remove());
+        assert_ok!(sess.checkpoint("name=to"));
+        // Changes after the second checkpoint aren't part of the delta.
+        put("e", "5");
+        put("a", "10");
+
+        assert_eq!(
+            assert_ok!(sess.checkpoint_delta("table:delta", "from", "to")),
+            [
+                Change::Modified {
+                    key: b"b\0".to_vec(),
+                    old_value: b"2\0".to_vec(),
+                    new_value: b"20\0".to_vec(),
+                },
+                Change::Removed {
+                    key: b"c\0".to_vec(),
+                    value: b"3\0".to_vec(),
+                },
+                Change::Added {
+                    key: b"d\0".to_vec(),
+                    value: b"4\0".to_vec(),
+                },
+            ]
+        );
+        assert!(assert_ok!(sess.checkpoint_delta("table:delta", "to", "to")).is_empty());
+    }
+}
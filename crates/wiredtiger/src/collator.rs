@@ -0,0 +1,117 @@
+use crate::raw_api::Result;
+use crate::Connection;
+use libc::c_int;
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+use wiredtiger_sys as wtffi;
+
+/// A key comparison function, called with the packed bytes of two keys.
+pub type CollatorFn = Box<dyn Fn(&[u8], &[u8]) -> Ordering + Send + Sync>;
+
+// WiredTiger only ever sees a pointer to `collator`. Because it's the first field
+// of a repr(C) struct, the callbacks can cast that pointer back to the whole
+// struct to reach the closure.
+#[repr(C)]
+struct RustCollator {
+    collator: wtffi::WT_COLLATOR,
+    compare: CollatorFn,
+}
+
+unsafe fn item_bytes<'a>(item: *const wtffi::WT_ITEM) -> &'a [u8] {
+    if item.is_null() || (*item).data.is_null() || (*item).size == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts((*item).data as *const u8, (*item).size)
+    }
+}
+
+unsafe extern "C" fn compare(
+    collator: *mut wtffi::WT_COLLATOR,
+    _session: *mut wtffi::WT_SESSION,
+    key1: *const wtffi::WT_ITEM,
+    key2: *const wtffi::WT_ITEM,
+    cmp: *mut c_int,
+) -> c_int {
+    let this = &*(collator as *const RustCollator);
+    let (a, b) = (item_bytes(key1), item_bytes(key2));
+    // Unwinding across the FFI boundary is undefined behavior, so a panicking
+    // closure is reported to WiredTiger as an error instead.
+    match panic::catch_unwind(AssertUnwindSafe(|| (this.compare)(a, b))) {
+        Ok(ordering) => {
+            *cmp = ordering as c_int;
+            0
+        }
+        Err(_) => wtffi::WT_ERROR,
+    }
+}
+
+unsafe extern "C" fn terminate(
+    collator: *mut wtffi::WT_COLLATOR,
+    _session: *mut wtffi::WT_SESSION,
+) -> c_int {
+    drop(Box::from_raw(collator as *mut RustCollator));
+    0
+}
+
+impl Connection {
+    /// Registers `collator` as a custom key ordering named `name`, which tables and
+    /// indices can then select with `collator=<name>`. The closure is kept alive
+    /// until the connection is closed, when WiredTiger hands it back to be freed.
+    pub fn add_collator(&self, name: &str, collator: CollatorFn) -> Result<()> {
+        let raw = Box::into_raw(Box::new(RustCollator {
+            collator: wtffi::WT_COLLATOR {
+                compare: Some(compare),
+                customize: None,
+                terminate: Some(terminate),
+            },
+            compare: collator,
+        }));
+        let result = self
+            .raw_conn()
+            .add_collator(name, raw as *mut wtffi::WT_COLLATOR);
+        if result.is_err() {
+            // WiredTiger didn't take ownership, so terminate will never be called.
+            drop(unsafe { Box::from_raw(raw) });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_reverse_collator() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        assert_ok!(conn.add_collator("reverse", Box::new(|a, b| b.cmp(a))));
+
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create(
+            "table:people",
+            "key_format=S,value_format=S,columns=(name,city)"
+        ));
+        assert_ok!(sess.create("index:people:city", "columns=(city),collator=reverse"));
+
+        let cur = assert_ok!(sess.open_cursor("table:people"));
+        for (name, city) in [("tyler", "boston"), ("mike", "austin"), ("sam", "chicago")] {
+            assert_ok!(cur.set_key(name));
+            assert_ok!(cur.set_value(city));
+            assert_ok!(cur.insert());
+        }
+
+        // The primary table is in normal order, the index in reverse.
+        let idx = assert_ok!(sess.open_cursor("index:people:city"));
+        let mut cities = Vec::new();
+        while idx.next().is_ok() {
+            let (_, city) = assert_ok!(idx.get_raw_key_value());
+            cities.push(String::from_utf8(city.unwrap()).unwrap());
+        }
+        assert_eq!(cities, ["chicago", "boston", "austin"]);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::event::RustEventHandler;
+use crate::raw_api::Result;
+use crate::{Connection, EventHandler, Session};
+use std::sync::Mutex;
+
+/// The outcome of `Connection::compact_all`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Tables that were compacted (whether or not that reclaimed anything).
+    pub compacted: Vec<String>,
+    /// Tables WiredTiger refused to compact, with the reason.
+    pub skipped: Vec<(String, String)>,
+    /// The total shrinkage of the compacted tables' files, in bytes.
+    pub bytes_reclaimed: u64,
+}
+
+impl Connection {
+    /// Compacts every table in the database. Tables that can't be compacted
+    /// (because they're busy, for instance, or their size can't be looked up) are
+    /// reported in `skipped` rather than failing the whole operation.
+    pub fn compact_all(&self) -> Result<CompactReport> {
+        let session = self.open_session()?;
+        let mut report = CompactReport::default();
+        for (uri, _) in self.list_tables()? {
+            let before = match session.file_size(&uri) {
+                Ok(size) => size,
+                Err(err) => {
+                    report.skipped.push((uri, err.message));
+                    continue;
+                }
+            };
+            if let Err(err) = session.compact(&uri, "") {
+                report.skipped.push((uri, err.message));
+                continue;
+            }
+            // The table was compacted either way; it's only the saving that
+            // can't be counted.
+            if let Ok(after) = session.file_size(&uri) {
+                report.bytes_reclaimed += before.saturating_sub(after);
+            }
+            report.compacted.push(uri);
+        }
+        Ok(report)
+    }
+}
+
+// Passes the progress reports of one compaction to its callback, and anything
+// else on to the connection's handler, if it has one.
+struct CompactProgress<'f> {
+    conn: &'f Connection,
+    progress: Mutex<&'f mut dyn FnMut(u64)>,
+}
+
+// `EventHandler`s must be `Send + Sync`, but this one is only called by the
+// thread running the compaction, while `compact_with_progress` waits for it.
+unsafe impl Send for CompactProgress<'_> {}
+unsafe impl Sync for CompactProgress<'_> {}
+
+impl EventHandler for CompactProgress<'_> {
+    fn on_error(&self, code: i32, message: &str) {
+        match &self.conn.inner.event_handler {
+            Some(handler) => handler.inner().on_error(code, message),
+            None => eprintln!("{}", message),
+        }
+    }
+
+    fn on_message(&self, message: &str) {
+        match &self.conn.inner.event_handler {
+            Some(handler) => handler.inner().on_message(message),
+            None => println!("{}", message),
+        }
+    }
+
+    fn on_progress(&self, _operation: &str, progress: u64) {
+        (self.progress.lock().unwrap())(progress);
+    }
+}
+
+impl Session {
+    /// Like `compact`, but calls `progress` with each of the progress reports
+    /// WiredTiger makes while compacting `name`, a count of the work done so far
+    /// that never goes down, so a long compaction can be followed.
+    pub fn compact_with_progress(
+        &self,
+        name: &str,
+        config: &str,
+        mut progress: impl FnMut(u64),
+    ) -> Result<()> {
+        // WiredTiger reports progress to the handler of the session doing the
+        // work, so the compaction gets a session of its own.
+        let mut handler = RustEventHandler::new(Box::new(CompactProgress {
+            conn: &self.conn,
+            progress: Mutex::new(&mut progress),
+        }));
+        let session = self
+            .conn
+            .raw_conn()
+            .open_session_with_event_handler("", &mut handler.handler)?;
+        let result = session.compact(name, config);
+        // The handler must outlive the session.
+        session.close()?;
+        drop(handler);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_compact_all() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let value = "x".repeat(200);
+        let tables = ["table:a", "table:b", "table:c"];
+
+        // WiredTiger won't bother compacting files under a megabyte, so write a
+        // few megabytes per table and then delete most of it.
+        for uri in tables {
+            assert_ok!(sess.create(uri, "key_format=S,value_format=S"));
+            let cur = assert_ok!(sess.open_cursor(uri));
+            for i in 0..20_000 {
+                assert_ok!(cur.set_key(&format!("{:06}", i)));
+                assert_ok!(cur.set_value(&value));
+                assert_ok!(cur.insert());
+            }
+            assert_ok!(sess.checkpoint(""));
+            for i in 0..18_000 {
+                assert_ok!(cur.set_key(&format!("{:06}", i)));
+                assert_ok!(cur.remove());
+            }
+        }
+        assert_ok!(sess.checkpoint(""));
+
+        let report = assert_ok!(conn.compact_all());
+        assert_eq!(report.compacted, tables);
+        assert!(report.skipped.is_empty());
+        assert!(report.bytes_reclaimed > 0);
+    }
+
+    #[test]
+    fn test_compact_with_progress() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let value = "x".repeat(200);
+        assert_ok!(sess.create("table:t", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:t"));
+        for i in 0..20_000 {
+            assert_ok!(cur.set_key(&format!("{:06}", i)));
+            assert_ok!(cur.set_value(&value));
+            assert_ok!(cur.insert());
+        }
+        assert_ok!(sess.checkpoint(""));
+        for i in 0..18_000 {
+            assert_ok!(cur.set_key(&format!("{:06}", i)));
+            assert_ok!(cur.remove());
+        }
+        drop(cur);
+        assert_ok!(sess.checkpoint(""));
+
+        // The reports are WiredTiger's own, made while it compacts, rather
+        // than one per file once it's done.
+        let mut reports = Vec::new();
+        assert_ok!(sess.compact_with_progress("table:t", "", |done| reports.push(done)));
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::raw_api::Result;
+use crate::Connection;
+use libc::c_int;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use wiredtiger_sys as wtffi;
+
+/// A block compression algorithm that WiredTiger can use for on-disk pages,
+/// selected per table with `block_compressor=<name>`.
+pub trait Compressor: Send + Sync {
+    /// Compresses a page. If the result doesn't fit in the space WiredTiger
+    /// offers (see `pre_size`), the page is written uncompressed instead.
+    fn compress(&self, src: &[u8]) -> Vec<u8>;
+
+    /// Decompresses a page previously produced by `compress`. `dst_len` is the
+    /// size of the original, uncompressed page.
+    fn decompress(&self, src: &[u8], dst_len: usize) -> Vec<u8>;
+
+    /// The largest output `compress` can produce for `src`. Defaults to the input
+    /// size, i.e. compression that doesn't shrink a page is abandoned.
+    fn pre_size(&self, src: &[u8]) -> usize {
+        src.len()
+    }
+}
+
+// Laid out so a `*mut WT_COMPRESSOR` handed back by WiredTiger can be cast to the
+// whole struct; see `RustCollator`.
+#[repr(C)]
+struct RustCompressor {
+    compressor: wtffi::WT_COMPRESSOR,
+    inner: Box<dyn Compressor>,
+}
+
+unsafe fn compressor<'a>(compressor: *mut wtffi::WT_COMPRESSOR) -> &'a dyn Compressor {
+    &*(*(compressor as *const RustCompressor)).inner
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+unsafe extern "C" fn compress(
+    this: *mut wtffi::WT_COMPRESSOR,
+    _session: *mut wtffi::WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_len: usize,
+    result_lenp: *mut usize,
+    compression_failed: *mut c_int,
+) -> c_int {
+    let src = bytes(src, src_len);
+    let Ok(out) = panic::catch_unwind(AssertUnwindSafe(|| compressor(this).compress(src))) else {
+        return wtffi::WT_ERROR;
+    };
+    if out.len() > dst_len {
+        *compression_failed = 1;
+        return 0;
+    }
+    ptr_copy(&out, dst);
+    *compression_failed = 0;
+    *result_lenp = out.len();
+    0
+}
+
+unsafe extern "C" fn decompress(
+    this: *mut wtffi::WT_COMPRESSOR,
+    _session: *mut wtffi::WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_len: usize,
+    result_lenp: *mut usize,
+) -> c_int {
+    let src = bytes(src, src_len);
+    let Ok(out) = panic::catch_unwind(AssertUnwindSafe(|| {
+        compressor(this).decompress(src, dst_len)
+    })) else {
+        return wtffi::WT_ERROR;
+    };
+    if out.len() > dst_len {
+        return libc::EINVAL;
+    }
+    ptr_copy(&out, dst);
+    *result_lenp = out.len();
+    0
+}
+
+unsafe extern "C" fn pre_size(
+    this: *mut wtffi::WT_COMPRESSOR,
+    _session: *mut wtffi::WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    result_lenp: *mut usize,
+) -> c_int {
+    let src = bytes(src, src_len);
+    match panic::catch_unwind(AssertUnwindSafe(|| compressor(this).pre_size(src))) {
+        Ok(len) => {
+            *result_lenp = len;
+            0
+        }
+        Err(_) => wtffi::WT_ERROR,
+    }
+}
+
+unsafe extern "C" fn terminate(
+    this: *mut wtffi::WT_COMPRESSOR,
+    _session: *mut wtffi::WT_SESSION,
+) -> c_int {
+    drop(Box::from_raw(this as *mut RustCompressor));
+    0
+}
+
+unsafe fn ptr_copy(src: &[u8], dst: *mut u8) {
+    std::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+}
+
+impl Connection {
+    /// Registers `compressor` under `name`, so tables created with
+    /// `block_compressor=<name>` use it. It's dropped when the connection closes.
+    pub fn add_compressor(&self, name: &str, compressor: Box<dyn Compressor>) -> Result<()> {
+        let raw = Box::into_raw(Box::new(RustCompressor {
+            compressor: wtffi::WT_COMPRESSOR {
+                compress: Some(compress),
+                decompress: Some(decompress),
+                pre_size: Some(pre_size),
+                terminate: Some(terminate),
+            },
+            inner: compressor,
+        }));
+        let result = self
+            .raw_conn()
+            .add_compressor(name, raw as *mut wtffi::WT_COMPRESSOR);
+        if result.is_err() {
+            drop(unsafe { Box::from_raw(raw) });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compressor;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Run-length encodes pages as (count, byte) pairs, counting how often
+    // WiredTiger hands it one to compress or decompress.
+    #[derive(Default)]
+    struct RunLength {
+        compressed: AtomicUsize,
+        decompressed: AtomicUsize,
+    }
+
+    struct Shared(Arc<RunLength>);
+
+    impl Compressor for Shared {
+        fn compress(&self, src: &[u8]) -> Vec<u8> {
+            self.0.compressed.fetch_add(1, Ordering::SeqCst);
+            let mut out = Vec::new();
+            for &byte in src {
+                match out.len() {
+                    len if len >= 2 && out[len - 1] == byte && out[len - 2] < u8::MAX => {
+                        out[len - 2] += 1
+                    }
+                    _ => out.extend([1, byte]),
+                }
+            }
+            out
+        }
+
+        fn decompress(&self, src: &[u8], dst_len: usize) -> Vec<u8> {
+            self.0.decompressed.fetch_add(1, Ordering::SeqCst);
+            let mut out = Vec::with_capacity(dst_len);
+            for pair in src.chunks(2) {
+                out.resize(out.len() + pair[0] as usize, pair[1]);
+            }
+            out
+        }
+    }
+
+    #[test]
+    fn test_custom_compressor() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let counts = Arc::new(RunLength::default());
+        // Long runs, so the pages really do shrink: WiredTiger writes a page
+        // uncompressed, and never decompresses it, if compressing doesn't help.
+        let rows: Vec<(String, String)> = (0..1000)
+            .map(|i| (format!("key{:04}", i), "x".repeat(200 + i % 10)))
+            .collect();
+
+        {
+            let conn = assert_ok!(Connection::open(path, "create"));
+            assert_ok!(conn.add_compressor("rle", Box::new(Shared(counts.clone()))));
+            let sess = assert_ok!(conn.open_session());
+            assert_ok!(sess.create(
+                "table:compressed",
+                "key_format=S,value_format=S,block_compressor=rle"
+            ));
+            let cur = assert_ok!(sess.open_cursor("table:compressed"));
+            for (k, v) in &rows {
+                assert_ok!(cur.set_key(k));
+                assert_ok!(cur.set_value(v));
+                assert_ok!(cur.insert());
+            }
+            // Force the pages through the compressor.
+            assert_ok!(sess.checkpoint(""));
+        }
+        assert!(counts.compressed.load(Ordering::SeqCst) > 0);
+
+        // Reopen so the data has to be read back (and decompressed) from disk.
+        let conn = assert_ok!(Connection::open(path, ""));
+        assert_ok!(conn.add_compressor("rle", Box::new(Shared(counts.clone()))));
+        let sess = assert_ok!(conn.open_session());
+        let cur = assert_ok!(sess.open_cursor("table:compressed"));
+        for (k, v) in &rows {
+            assert_ok!(cur.next());
+            let (key, value) = assert_ok!(cur.get_raw_key_value());
+            assert_eq!(assert_ok!(std::str::from_utf8(&key.unwrap())), k);
+            assert_eq!(assert_ok!(std::str::from_utf8(&value.unwrap())), v);
+        }
+        assert!(cur.next().unwrap_err().is_not_found());
+        assert!(counts.decompressed.load(Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn test_run_length_round_trip() {
+        let rle = Shared(Arc::default());
+        let page = [vec![7u8; 300], b"abc".to_vec(), vec![0; 5]].concat();
+        let compressed = rle.compress(&page);
+        assert!(compressed.len() < page.len());
+        assert_eq!(rle.decompress(&compressed, page.len()), page);
+    }
+}
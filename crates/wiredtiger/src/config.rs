@@ -1,3 +1,9 @@
+use crate::format::fields;
+use crate::raw_api::{Error, Result};
+use std::fmt;
+
+/// Typed configuration for `Connection::open_with`, built with
+/// `OpenConnectionConfig::builder()`. Its `Display` form is the config string.
 pub struct OpenConnectionConfig {
     // in-memory alignment (in bytes) for buffers used for I/O.
     // The default value of -1 indicates a platform-specific alignment value should be used
@@ -16,7 +22,7 @@ pub struct OpenConnectionConfig {
     // Maximum heap memory to allocate for the cache.
     // A database should configure either cache_size or shared_cache but not both.
     // An integer between 1MB and 10TB; default 100MB.
-    cache_size: u32,
+    cache_size: u64,
 
     checkpoint: CheckpointConfig,
 
@@ -69,7 +75,7 @@ pub struct OpenConnectionConfig {
     // in allocations of the set size, instead of a block at a time as each
     // new block is written. For example, file_extend=(data=16MB).
     // A list, with values chosen from the following options: "data", "log"; default empty.
-    file_extend: Vec<FileExtensionConfigOption>,
+    file_extend: Vec<(FileExtensionConfigOption, u64)>,
 
     // Maximum number of simultaneous hazard pointers per session handle.
     // An integer greater than or equal to 15; default 1000.
@@ -102,6 +108,8 @@ pub struct OpenConnectionConfig {
     // See Database Home Directory for more information. A boolean flag; default false.
     use_environment_priv: bool,
 
+    tiered_storage: TieredStorageConfig,
+
     // Enable messages for various events.
     // Only available if WiredTiger is configured with –enable-verbose.
     // Options are given as a list, such as "verbose=[evictserver,read]".
@@ -114,13 +122,314 @@ pub struct OpenConnectionConfig {
     verbose: Vec<VerboseOption>,
 }
 
+impl Default for OpenConnectionConfig {
+    fn default() -> Self {
+        Self {
+            buffer_alignment: -1,
+            cache_overhead: 8,
+            cache_size: 100 * MB,
+            checkpoint: CheckpointConfig::default(),
+            checkpoint_sync: true,
+            config_base: true,
+            create: false,
+            direct_io: Vec::new(),
+            error_prefix: String::new(),
+            eviction: EvictionConfig::default(),
+            eviction_dirty_target: 80,
+            eviction_target: 80,
+            eviction_trigger: 95,
+            exclusive: false,
+            extensions: Vec::new(),
+            file_extend: Vec::new(),
+            hazard_max: 1000,
+            log: LogConfig::default(),
+            shared_cache: SharedCacheConfig::default(),
+            statistics: Vec::new(),
+            statistics_log: StatisticsLogConfig::default(),
+            transaction_sync: TransactionSyncConfig::default(),
+            use_environment_priv: false,
+            tiered_storage: TieredStorageConfig::default(),
+            verbose: Vec::new(),
+        }
+    }
+}
+
 impl OpenConnectionConfig {
-    pub fn to_string(&self) -> String {
-        "".to_string()
+    pub fn builder() -> OpenConnectionConfigBuilder {
+        OpenConnectionConfigBuilder {
+            config: Self::default(),
+        }
+    }
+}
+
+/// Builds an `OpenConnectionConfig`; anything not set keeps WiredTiger's default.
+pub struct OpenConnectionConfigBuilder {
+    config: OpenConnectionConfig,
+}
+
+impl OpenConnectionConfigBuilder {
+    /// Create the database if it doesn't exist.
+    pub fn create(mut self, create: bool) -> Self {
+        self.config.create = create;
+        self
+    }
+
+    /// Fail if the database already exists; used together with `create`.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.config.exclusive = exclusive;
+        self
+    }
+
+    /// The alignment of I/O buffers, in bytes, up to 1MB. -1 (the default) picks
+    /// a platform-specific alignment.
+    pub fn buffer_alignment(mut self, bytes: i32) -> Result<Self> {
+        if !(-1..=MB as i32).contains(&bytes) {
+            return Err(Error::new(format!(
+                "buffer_alignment must be between -1 and 1MB, got {}",
+                bytes
+            )));
+        }
+        self.config.buffer_alignment = bytes;
+        Ok(self)
+    }
+
+    /// The heap allocator's overhead as a percentage, between 0 and 30, by which
+    /// to scale cache usage; e.g. 10 counts 10GB of cached data as 11GB.
+    pub fn cache_overhead(mut self, percent: u8) -> Result<Self> {
+        if percent > 30 {
+            return Err(Error::new(format!(
+                "cache_overhead must be between 0 and 30, got {}",
+                percent
+            )));
+        }
+        self.config.cache_overhead = percent;
+        Ok(self)
+    }
+
+    /// The maximum heap memory for the cache, in megabytes.
+    pub fn cache_size_mb(mut self, megabytes: u64) -> Self {
+        self.config.cache_size = megabytes * MB;
+        self
+    }
+
+    /// Whether to flush files to stable storage on close and checkpoint.
+    pub fn checkpoint_sync(mut self, sync: bool) -> Self {
+        self.config.checkpoint_sync = sync;
+        self
+    }
+
+    /// A prefix for WiredTiger's error messages.
+    pub fn error_prefix(mut self, prefix: &str) -> Self {
+        self.config.error_prefix = prefix.to_string();
+        self
+    }
+
+    /// The minimum and maximum number of eviction threads.
+    pub fn eviction_threads(mut self, min: u8, max: u8) -> Self {
+        self.config.eviction.threads_min = min;
+        self.config.eviction.threads_max = max;
+        self
+    }
+
+    /// Enable the write-ahead log.
+    pub fn log_enabled(mut self, enabled: bool) -> Self {
+        self.config.log.enabled = enabled;
+        self
+    }
+
+    /// Which statistics to maintain.
+    pub fn statistics(mut self, options: &[StatisticsOption]) -> Self {
+        self.config.statistics = options.to_vec();
+        self
+    }
+
+    /// Keep tables in shared storage, such as an S3 bucket; see
+    /// `TieredStorageConfig`.
+    pub fn tiered_storage(mut self, tiered_storage: TieredStorageConfig) -> Self {
+        self.config.tiered_storage = tiered_storage;
+        self
+    }
+
+    /// Write statistics to `WiredTigerStat.*` files in the home directory every
+    /// `wait` seconds, including those of the open objects matching `sources`
+    /// (e.g. `table:` for every table). Statistics have to be enabled too.
+    pub fn statistics_log(mut self, wait: u16, sources: &[&str]) -> Self {
+        self.config.statistics_log.wait = wait;
+        self.config.statistics_log.sources = sources.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Which categories of verbose messages to emit.
+    pub fn verbose(mut self, options: &[VerboseOption]) -> Self {
+        self.config.verbose = options.to_vec();
+        self
+    }
+
+    pub fn build(self) -> OpenConnectionConfig {
+        self.config
+    }
+}
+
+// Emits only the fields that differ from the defaults, e.g.
+// `create=true,cache_size=500MB,eviction=(threads_max=4),statistics=[fast]`.
+impl fmt::Display for OpenConnectionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = Self::default();
+        let mut w = ConfigWriter::default();
+        if self.buffer_alignment != d.buffer_alignment {
+            w.push("buffer_alignment", self.buffer_alignment);
+        }
+        if self.cache_overhead != d.cache_overhead {
+            w.push("cache_overhead", self.cache_overhead);
+        }
+        if self.cache_size != d.cache_size {
+            w.push("cache_size", size(self.cache_size));
+        }
+        w.sub("checkpoint", |w| self.checkpoint.write_config(w));
+        if self.checkpoint_sync != d.checkpoint_sync {
+            w.push("checkpoint_sync", self.checkpoint_sync);
+        }
+        if self.config_base != d.config_base {
+            w.push("config_base", self.config_base);
+        }
+        if self.create != d.create {
+            w.push("create", self.create);
+        }
+        if !self.direct_io.is_empty() {
+            w.list(
+                "direct_io",
+                self.direct_io.iter().map(DirectIOSetting::as_config_token),
+            );
+        }
+        w.string("error_prefix", &self.error_prefix);
+        w.sub("eviction", |w| self.eviction.write_config(w));
+        if self.eviction_dirty_target != d.eviction_dirty_target {
+            w.push("eviction_dirty_target", self.eviction_dirty_target);
+        }
+        if self.eviction_target != d.eviction_target {
+            w.push("eviction_target", self.eviction_target);
+        }
+        if self.eviction_trigger != d.eviction_trigger {
+            w.push("eviction_trigger", self.eviction_trigger);
+        }
+        if self.exclusive != d.exclusive {
+            w.push("exclusive", self.exclusive);
+        }
+        if !self.extensions.is_empty() {
+            // Passed through untouched, since each entry may carry its own
+            // `path={config}`.
+            w.list("extensions", self.extensions.iter());
+        }
+        if !self.file_extend.is_empty() {
+            w.sub("file_extend", |w| {
+                for (kind, len) in &self.file_extend {
+                    w.push(kind.as_config_token(), size(*len));
+                }
+            });
+        }
+        if self.hazard_max != d.hazard_max {
+            w.push("hazard_max", self.hazard_max);
+        }
+        self.log.write_config(&mut w);
+        w.sub("shared_cache", |w| self.shared_cache.write_config(w));
+        if !self.statistics.is_empty() {
+            w.list(
+                "statistics",
+                self.statistics
+                    .iter()
+                    .map(StatisticsOption::as_config_token),
+            );
+        }
+        w.sub("statistics_log", |w| self.statistics_log.write_config(w));
+        w.sub("transaction_sync", |w| {
+            self.transaction_sync.write_config(w)
+        });
+        if self.use_environment_priv != d.use_environment_priv {
+            w.push("use_environment_priv", self.use_environment_priv);
+        }
+        w.sub("tiered_storage", |w| self.tiered_storage.write_config(w));
+        if !self.verbose.is_empty() {
+            w.list(
+                "verbose",
+                self.verbose.iter().map(VerboseOption::as_config_token),
+            );
+        }
+        write!(f, "{}", w)
+    }
+}
+
+const KB: u64 = 1 << 10;
+const MB: u64 = 1 << 20;
+const GB: u64 = 1 << 30;
+const TB: u64 = 1 << 40;
+
+// Formats a byte count the way WiredTiger's documentation writes sizes, e.g.
+// `500MB`, falling back to a plain number of bytes.
+fn size(bytes: u64) -> String {
+    for (unit, suffix) in [(TB, "TB"), (GB, "GB"), (MB, "MB"), (KB, "KB")] {
+        if bytes != 0 && bytes.is_multiple_of(unit) {
+            return format!("{}{}", bytes / unit, suffix);
+        }
     }
+    bytes.to_string()
 }
 
-enum VerboseOption {
+// Strings containing config syntax (or spaces) have to be quoted.
+pub(crate) fn quote(s: &str) -> String {
+    if s.is_empty() || s.contains(|c: char| c.is_whitespace() || ",=:()[]{}\"".contains(c)) {
+        format!("{:?}", s)
+    } else {
+        s.to_string()
+    }
+}
+
+// Accumulates `key=value` pairs for a config string.
+#[derive(Default)]
+struct ConfigWriter {
+    entries: Vec<String>,
+}
+
+impl ConfigWriter {
+    fn push<V: fmt::Display>(&mut self, key: &str, value: V) {
+        self.entries.push(format!("{}={}", key, value));
+    }
+
+    // Strings are left out when empty.
+    fn string(&mut self, key: &str, value: &str) {
+        if !value.is_empty() {
+            self.push(key, quote(value));
+        }
+    }
+
+    // `key=[a,b,c]`
+    fn list<I>(&mut self, key: &str, values: I)
+    where
+        I: Iterator,
+        I::Item: fmt::Display,
+    {
+        let values: Vec<String> = values.map(|v| v.to_string()).collect();
+        self.push(key, format!("[{}]", values.join(",")));
+    }
+
+    // `key=(...)`, omitted entirely if the nested config is empty.
+    fn sub<F: FnOnce(&mut ConfigWriter)>(&mut self, key: &str, f: F) {
+        let mut nested = ConfigWriter::default();
+        f(&mut nested);
+        if !nested.entries.is_empty() {
+            self.push(key, format!("({})", nested));
+        }
+    }
+}
+
+impl fmt::Display for ConfigWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.entries.join(","))
+    }
+}
+
+/// A category of verbose messages, for `verbose=[...]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerboseOption {
     Api,
     Block,
     Checkpoint,
@@ -146,6 +455,37 @@ enum VerboseOption {
     Write,
 }
 
+impl VerboseOption {
+    /// The option's name in a config string.
+    pub fn as_config_token(&self) -> &'static str {
+        match self {
+            VerboseOption::Api => "api",
+            VerboseOption::Block => "block",
+            VerboseOption::Checkpoint => "checkpoint",
+            VerboseOption::Compact => "compact",
+            VerboseOption::Evict => "evict",
+            VerboseOption::EvictServer => "evictserver",
+            VerboseOption::FileOps => "fileops",
+            VerboseOption::Log => "log",
+            VerboseOption::Lsm => "lsm",
+            VerboseOption::Metadata => "metadata",
+            VerboseOption::Mutex => "mutex",
+            VerboseOption::Overflow => "overflow",
+            VerboseOption::Read => "read",
+            VerboseOption::Reconcile => "reconcile",
+            VerboseOption::Recovery => "recovery",
+            VerboseOption::Salvage => "salvage",
+            VerboseOption::SharedCache => "shared_cache",
+            VerboseOption::Split => "split",
+            VerboseOption::Temporary => "temporary",
+            VerboseOption::Transaction => "transaction",
+            VerboseOption::Verify => "verify",
+            VerboseOption::Version => "version",
+            VerboseOption::Write => "write",
+        }
+    }
+}
+
 // How to sync log records when the transaction commits.
 struct TransactionSyncConfig {
     //  Whether to sync the log on every commit by default,
@@ -159,12 +499,47 @@ struct TransactionSyncConfig {
     method: SyncMethodOption,
 }
 
-enum SyncMethodOption {
+impl Default for TransactionSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: SyncMethodOption::FSync,
+        }
+    }
+}
+
+impl TransactionSyncConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.enabled != d.enabled {
+            w.push("enabled", self.enabled);
+        }
+        if self.method != d.method {
+            w.push("method", self.method.as_config_token());
+        }
+    }
+}
+
+/// How log records are made durable on commit, for
+/// `transaction_sync=(method=...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMethodOption {
     DSync,
     FSync,
     None,
 }
 
+impl SyncMethodOption {
+    /// The option's name in a config string.
+    pub fn as_config_token(&self) -> &'static str {
+        match self {
+            SyncMethodOption::DSync => "dsync",
+            SyncMethodOption::FSync => "fsync",
+            SyncMethodOption::None => "none",
+        }
+    }
+}
+
 struct StatisticsLogConfig {
     // log statistics on database close.	a boolean flag; default false.
     on_close: bool,
@@ -189,13 +564,60 @@ struct StatisticsLogConfig {
     wait: u16,
 }
 
-enum StatisticsOption {
+impl Default for StatisticsLogConfig {
+    fn default() -> Self {
+        Self {
+            on_close: false,
+            path: "WiredTigerStat.%d.%H".to_string(),
+            sources: Vec::new(),
+            timestamp: "%b %d %H:%M:%S".to_string(),
+            wait: 0,
+        }
+    }
+}
+
+impl StatisticsLogConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.on_close != d.on_close {
+            w.push("on_close", self.on_close);
+        }
+        if self.path != d.path {
+            w.push("path", quote(&self.path));
+        }
+        if !self.sources.is_empty() {
+            w.list("sources", self.sources.iter().map(|s| quote(s)));
+        }
+        if self.timestamp != d.timestamp {
+            w.push("timestamp", quote(&self.timestamp));
+        }
+        if self.wait != d.wait {
+            w.push("wait", self.wait);
+        }
+    }
+}
+
+/// Which statistics the connection maintains, for `statistics=[...]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsOption {
     All,
     Fast,
     None,
     Clear,
 }
 
+impl StatisticsOption {
+    /// The option's name in a config string.
+    pub fn as_config_token(&self) -> &'static str {
+        match self {
+            StatisticsOption::All => "all",
+            StatisticsOption::Fast => "fast",
+            StatisticsOption::None => "none",
+            StatisticsOption::Clear => "clear",
+        }
+    }
+}
+
 struct LogConfig {
     // Automatically archive unneeded log files. Default true.
     archive: bool,
@@ -237,6 +659,61 @@ struct LogConfig {
     session_max: u16,
 }
 
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            archive: true,
+            compressor: "none".to_string(),
+            enabled: false,
+            file_max: 100 * MB as i32,
+            path: String::new(),
+            prealloc: true,
+            recover: "on".to_string(),
+            mmap: true,
+            multiprocess: false,
+            session_max: 100,
+        }
+    }
+}
+
+impl LogConfig {
+    // Writes `log=(...)`. `mmap`, `multiprocess` and `session_max` are
+    // connection-level settings in WiredTiger, so they're written at the top level.
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        w.sub("log", |w| {
+            if self.archive != d.archive {
+                w.push("archive", self.archive);
+            }
+            if self.compressor != d.compressor && !self.compressor.is_empty() {
+                w.push("compressor", quote(&self.compressor));
+            }
+            if self.enabled != d.enabled {
+                w.push("enabled", self.enabled);
+            }
+            if self.file_max != d.file_max {
+                w.push("file_max", size(self.file_max as u64));
+            }
+            w.string("path", &self.path);
+            if self.prealloc != d.prealloc {
+                w.push("prealloc", self.prealloc);
+            }
+            if self.recover != d.recover {
+                w.push("recover", quote(&self.recover));
+            }
+        });
+        if self.mmap != d.mmap {
+            w.push("mmap", self.mmap);
+        }
+        if self.multiprocess != d.multiprocess {
+            w.push("multiprocess", self.multiprocess);
+        }
+        if self.session_max != d.session_max {
+            w.push("session_max", self.session_max);
+        }
+    }
+}
+
 struct SharedCacheConfig {
     // The granularity that a shared cache is redistributed.
     // An integer between 1MB and 10TB; default 10MB.
@@ -256,11 +733,97 @@ struct SharedCacheConfig {
     size: u32,
 }
 
-enum FileExtensionConfigOption {
+impl Default for SharedCacheConfig {
+    fn default() -> Self {
+        Self {
+            chunk: 10 * MB as u32,
+            name: "none".to_string(),
+            reserve: 0,
+            size: 500 * MB as u32,
+        }
+    }
+}
+
+impl SharedCacheConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.chunk != d.chunk {
+            w.push("chunk", size(self.chunk as u64));
+        }
+        if self.name != d.name {
+            w.push("name", quote(&self.name));
+        }
+        if self.reserve != d.reserve {
+            w.push("reserve", size(self.reserve as u64));
+        }
+        if self.size != d.size {
+            w.push("size", size(self.size as u64));
+        }
+    }
+}
+
+/// Tiered storage settings, for `OpenConnectionConfigBuilder::tiered_storage`.
+/// Tables then keep their checkpointed data in objects that
+/// `Session::flush_tier` copies to shared storage, through the storage source
+/// extension `name` (e.g. `s3_store`, loaded with `extensions=[...]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TieredStorageConfig {
+    /// The storage source to use, or `none` for no tiered storage. Default
+    /// `none`.
+    pub name: String,
+    /// The bucket objects are stored in. Default empty.
+    pub bucket: String,
+    /// A prefix for the names of this database's objects in the bucket.
+    /// Default empty.
+    pub bucket_prefix: String,
+    /// How long, in seconds, flushed objects are kept locally as well. Default
+    /// 300.
+    pub local_retention: u32,
+}
+
+impl Default for TieredStorageConfig {
+    fn default() -> Self {
+        Self {
+            name: "none".to_string(),
+            bucket: String::new(),
+            bucket_prefix: String::new(),
+            local_retention: 300,
+        }
+    }
+}
+
+impl TieredStorageConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        w.string("bucket", &self.bucket);
+        w.string("bucket_prefix", &self.bucket_prefix);
+        if self.local_retention != d.local_retention {
+            w.push("local_retention", self.local_retention);
+        }
+        if self.name != d.name {
+            w.push("name", quote(&self.name));
+        }
+    }
+}
+
+/// A kind of file WiredTiger can extend ahead of writes, for
+/// `file_extend=(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileExtensionConfigOption {
     Data,
     Log,
 }
 
+impl FileExtensionConfigOption {
+    /// The option's name in a config string.
+    pub fn as_config_token(&self) -> &'static str {
+        match self {
+            FileExtensionConfigOption::Data => "data",
+            FileExtensionConfigOption::Log => "log",
+        }
+    }
+}
+
 struct EvictionConfig {
     // maximum number of threads WiredTiger will start to help evict pages from cache.
     // The number of threads started will vary depending on the current eviction load.
@@ -272,24 +835,95 @@ struct EvictionConfig {
     threads_min: u8,
 }
 
-enum DirectIOSetting {
+impl Default for EvictionConfig {
+    fn default() -> Self {
+        Self {
+            threads_max: 1,
+            threads_min: 1,
+        }
+    }
+}
+
+impl EvictionConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.threads_max != d.threads_max {
+            w.push("threads_max", self.threads_max);
+        }
+        if self.threads_min != d.threads_min {
+            w.push("threads_min", self.threads_min);
+        }
+    }
+}
+
+/// A kind of file accessed with `O_DIRECT`, for `direct_io=[...]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectIOSetting {
     Checkpoint,
     Data,
     Log,
 }
 
-struct CheckpointConfig {
-    // Wait for this amount of log record bytes to be written to the log between each checkpoint.
-    // A database can configure both log_size and wait to set an upper bound for checkpoints;
-    // Setting this value above 0 configures periodic checkpoints.	An integer between 0 and 2GB; default 0.
-    log_size: i32,
+impl DirectIOSetting {
+    /// The option's name in a config string.
+    pub fn as_config_token(&self) -> &'static str {
+        match self {
+            DirectIOSetting::Checkpoint => "checkpoint",
+            DirectIOSetting::Data => "data",
+            DirectIOSetting::Log => "log",
+        }
+    }
+}
 
-    // The checkpoint name. Default "WiredTigerCheckpoint".
-    name: String,
+/// Periodic checkpoints, for `Connection::open_with_checkpoint`. WiredTiger runs
+/// them on a single server thread. Its `Display` form is the `checkpoint=(...)`
+/// setting, or nothing if every field is the default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointConfig {
+    /// Wait for this amount of log record bytes to be written to the log between each checkpoint.
+    /// A database can configure both log_size and wait to set an upper bound for checkpoints;
+    /// Setting this value above 0 configures periodic checkpoints. An integer between 0 and 2GB; default 0.
+    pub log_size: i32,
+
+    /// The checkpoint name. Default "WiredTigerCheckpoint".
+    pub name: String,
+
+    /// Seconds to wait between each checkpoint; setting this value above 0 configures periodic checkpoints.
+    /// An integer between 0 and 100000; default 0.
+    pub wait: i16,
+}
 
-    // Seconds to wait between each checkpoint; setting this value above 0 configures periodic checkpoints.
-    // An integer between 0 and 100000; default 0.
-    wait: i16,
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            log_size: 0,
+            name: "WiredTigerCheckpoint".to_string(),
+            wait: 0,
+        }
+    }
+}
+
+impl fmt::Display for CheckpointConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = ConfigWriter::default();
+        w.sub("checkpoint", |w| self.write_config(w));
+        write!(f, "{}", w)
+    }
+}
+
+impl CheckpointConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.log_size != d.log_size {
+            w.push("log_size", size(self.log_size as u64));
+        }
+        if self.name != d.name {
+            w.push("name", quote(&self.name));
+        }
+        if self.wait != d.wait {
+            w.push("wait", self.wait);
+        }
+    }
 }
 
 struct AsyncConfig {
@@ -314,32 +948,93 @@ struct LSMManagerConfig {
     worker_thread_max: u8,
 }
 
-struct OpenSessionConfig {
-    // The default isolation level for operations in this session.
-    // A string, chosen from the following options:
-    //  "read-uncommitted", "read-committed", "snapshot";
-    // Default read-committed.
-    isolation: IsolationLevel,
+/// Configuration for a session opened with `Connection::open_session_with`; pass
+/// its `Display` form, the config string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenSessionConfig {
+    /// The default isolation level for the session's transactions. `None` leaves
+    /// WiredTiger's default.
+    pub isolation: Option<IsolationLevel>,
+    /// Cache closed cursors for reuse, making reopening them cheaper at the cost
+    /// of keeping their resources. Default true.
+    pub cache_cursors: bool,
+}
+
+impl Default for OpenSessionConfig {
+    fn default() -> Self {
+        Self {
+            isolation: None,
+            cache_cursors: true,
+        }
+    }
+}
+
+impl fmt::Display for OpenSessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut w = ConfigWriter::default();
+        if !self.cache_cursors {
+            w.push("cache_cursors", false);
+        }
+        if let Some(isolation) = self.isolation {
+            w.push("isolation", isolation.token());
+        }
+        write!(f, "{}", w)
+    }
 }
 
-enum IsolationLevel {
+/// How much of other transactions' work a transaction can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
     ReadUncommitted,
     ReadCommitted,
     Snapshot,
 }
 
+impl IsolationLevel {
+    pub(crate) fn token(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "read-uncommitted",
+            IsolationLevel::ReadCommitted => "read-committed",
+            IsolationLevel::Snapshot => "snapshot",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BlockAllocationOption {
     First,
     Best,
 }
 
+impl BlockAllocationOption {
+    fn token(&self) -> &'static str {
+        match self {
+            BlockAllocationOption::First => "first",
+            BlockAllocationOption::Best => "best",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ChecksumOption {
     On,
     Off,
     Uncompressed,
 }
 
-struct CreateConfig {
+impl ChecksumOption {
+    fn token(&self) -> &'static str {
+        match self {
+            ChecksumOption::On => "on",
+            ChecksumOption::Off => "off",
+            ChecksumOption::Uncompressed => "uncompressed",
+        }
+    }
+}
+
+/// Typed configuration for `Session::create_with`, built with
+/// `CreateConfig::builder()`. Its `Display` form is the config string.
+pub struct CreateConfig {
     // The file unit allocation size, in bytes, must a power-of-two; smaller values decrease the file space required by overflow items, and the default value of 4KB is a good choice absent requirements from the operating system or storage device.	an integer between 512B and 128MB; default 4KB.
     allocation_size: u32,
 
@@ -408,7 +1103,7 @@ struct CreateConfig {
     //  If set, keys larger than the specified size are stored as overflow items (which may require additional I/O to access).
     //  The default and the maximum allowed value are both one-tenth the size of a newly split internal page.
     //	An integer greater than or equal to 0; default 0.
-    internal_key_max: u16,
+    internal_key_max: u32,
 
     // Configure internal key truncation, discarding unnecessary trailing bytes on internal keys (ignored for custom collators).	a boolean flag; default true.
     internal_key_truncate: bool,
@@ -417,7 +1112,7 @@ struct CreateConfig {
     //  the size must be a multiple of the allocation size and is significant for applications wanting to avoid excessive L2 cache misses while searching the tree.
     // The page maximum is the bytes of uncompressed data, that is, the limit is applied before any block compression is done.
     // An integer between 512B and 512MB; default 4KB.
-    internal_page_max: u16,
+    internal_page_max: u32,
 
     // The format of the data packed into key items.
     //  See Format types for details. By default, the key_format is 'u' and applications use WT_ITEM structures to manipulate raw byte arrays.
@@ -430,7 +1125,7 @@ struct CreateConfig {
     //(which may require additional I/O to access).
     // The default value is one-tenth the size of a newly split leaf page.
     // An integer greater than or equal to 0; default 0.
-    leaf_key_max: u16,
+    leaf_key_max: u32,
 
     // The maximum page size for leaf nodes, in bytes;
     // the size must be a multiple of the allocation size,
@@ -438,7 +1133,7 @@ struct CreateConfig {
     // sequential data transfer from a storage device.
     // The page maximum is the bytes of uncompressed data, that is,
     //  the limit is applied before any block compression is done. An integer between 512B and 512MB; default 32KB.
-    leaf_page_max: u16,
+    leaf_page_max: u32,
 
     // The largest value stored in a leaf node, in bytes.
     // If set, values larger than the specified size are stored as
@@ -447,7 +1142,7 @@ struct CreateConfig {
     // the page size is temporarily ignored when large values are written.
     // The default is one-half the size of a newly split leaf page.
     // An integer greater than or equal to 0; default 0.
-    leaf_value_max: u16,
+    leaf_value_max: u32,
 
     lsm_config: LSMConfig,
 
@@ -502,7 +1197,299 @@ struct CreateConfig {
     value_format: String,
 }
 
-struct DropConfig {
+impl Default for CreateConfig {
+    fn default() -> Self {
+        Self {
+            allocation_size: 4 * KB as u32,
+            app_metadata: String::new(),
+            block_allocation: BlockAllocationOption::Best,
+            block_compressor: String::new(),
+            cache_resident: false,
+            checksum: ChecksumOption::Uncompressed,
+            colgroups: Vec::new(),
+            collator: String::new(),
+            columns: Vec::new(),
+            dictionary: 0,
+            exclusive: false,
+            extractor: String::new(),
+            format: String::new(),
+            huffman_key: String::new(),
+            huffman_value: String::new(),
+            immutable: false,
+            internal_key_max: 0,
+            internal_key_truncate: true,
+            internal_page_max: 4 * KB as u32,
+            key_format: String::new(),
+            leaf_key_max: 0,
+            leaf_page_max: 32 * KB as u32,
+            leaf_value_max: 0,
+            lsm_config: LSMConfig::default(),
+            memory_page_max: 5 * MB as u32,
+            os_cache_dirty_max: 0,
+            os_cache_max: 0,
+            prefix_compression: false,
+            prefix_compression_min: 4,
+            split_pct: 75,
+            data_type: String::new(),
+            value_format: String::new(),
+        }
+    }
+}
+
+impl CreateConfig {
+    pub fn builder() -> CreateConfigBuilder {
+        CreateConfigBuilder {
+            config: Self::default(),
+        }
+    }
+}
+
+/// Page size combinations for common workloads, see
+/// `CreateConfigBuilder::workload_preset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Small leaf pages so point reads pull less data into cache:
+    /// `internal_page_max=16KB,leaf_page_max=16KB,memory_page_max=2MB`.
+    ReadHeavy,
+    /// Large in-memory pages so updates trigger fewer splits and reconciliations:
+    /// `internal_page_max=16KB,leaf_page_max=64KB,memory_page_max=20MB`.
+    WriteHeavy,
+    /// Bigger blocks and pages so values seldom spill into overflow items:
+    /// `allocation_size=16384,internal_page_max=64KB,leaf_page_max=128KB,memory_page_max=50MB`.
+    LargeValues,
+    /// Compact pages for many small records:
+    /// `leaf_page_max=8KB,memory_page_max=1MB`.
+    SmallKeys,
+}
+
+/// Builds a `CreateConfig`; anything not set keeps WiredTiger's default.
+pub struct CreateConfigBuilder {
+    config: CreateConfig,
+}
+
+impl CreateConfigBuilder {
+    /// The format of keys, e.g. `S` or `q`. See WiredTiger's format types.
+    pub fn key_format(mut self, format: &str) -> Self {
+        self.config.key_format = format.to_string();
+        self
+    }
+
+    /// The format of values, e.g. `S` or `Su`.
+    pub fn value_format(mut self, format: &str) -> Self {
+        self.config.value_format = format.to_string();
+        self
+    }
+
+    /// Names for the key columns followed by the value columns.
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.config.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Names of column groups, each of which must be created separately.
+    pub fn colgroups(mut self, colgroups: &[&str]) -> Self {
+        self.config.colgroups = colgroups.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// A block compressor, either built in (e.g. `snappy`) or registered with
+    /// `Connection::add_compressor`.
+    pub fn block_compressor(mut self, name: &str) -> Self {
+        self.config.block_compressor = name.to_string();
+        self
+    }
+
+    /// A collator registered with `Connection::add_collator`.
+    pub fn collator(mut self, name: &str) -> Self {
+        self.config.collator = name.to_string();
+        self
+    }
+
+    /// Fail if the object already exists.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.config.exclusive = exclusive;
+        self
+    }
+
+    /// The data source to store the object in: `file` (the default) or `lsm`.
+    pub fn data_type(mut self, data_type: &str) -> Result<Self> {
+        if !["file", "lsm"].contains(&data_type) {
+            return Err(Error::new(format!(
+                "type must be file or lsm, got {:?}",
+                data_type
+            )));
+        }
+        self.config.data_type = data_type.to_string();
+        Ok(self)
+    }
+
+    /// The file format. Only `btree` (the default) is supported.
+    pub fn format(mut self, format: &str) -> Result<Self> {
+        if format != "btree" {
+            return Err(Error::new(format!(
+                "format must be btree, got {:?}",
+                format
+            )));
+        }
+        self.config.format = format.to_string();
+        Ok(self)
+    }
+
+    /// The file allocation unit, in bytes: a power of two between 512B and 128MB.
+    pub fn allocation_size(mut self, bytes: u32) -> Result<Self> {
+        if !bytes.is_power_of_two() || !(512..=128 * MB as u32).contains(&bytes) {
+            return Err(Error::new(format!(
+                "allocation_size must be a power of two between 512B and 128MB, got {}",
+                bytes
+            )));
+        }
+        self.config.allocation_size = bytes;
+        Ok(self)
+    }
+
+    /// Enable prefix compression of row-store leaf pages.
+    pub fn prefix_compression(mut self, enabled: bool) -> Self {
+        self.config.prefix_compression = enabled;
+        self
+    }
+
+    /// Sets `allocation_size`, `internal_page_max`, `leaf_page_max` and
+    /// `memory_page_max` to suit `preset`, overriding any earlier values.
+    pub fn workload_preset(mut self, preset: Preset) -> Self {
+        let (allocation, internal, leaf, memory) = match preset {
+            Preset::ReadHeavy => (4 * KB, 16 * KB, 16 * KB, 2 * MB),
+            Preset::WriteHeavy => (4 * KB, 16 * KB, 64 * KB, 20 * MB),
+            Preset::LargeValues => (16 * KB, 64 * KB, 128 * KB, 50 * MB),
+            Preset::SmallKeys => (4 * KB, 4 * KB, 8 * KB, MB),
+        };
+        self.config.allocation_size = allocation as u32;
+        self.config.internal_page_max = internal as u32;
+        self.config.leaf_page_max = leaf as u32;
+        self.config.memory_page_max = memory as u32;
+        self
+    }
+
+    pub fn build(self) -> CreateConfig {
+        self.config
+    }
+}
+
+// Emits only the fields that differ from the defaults. Empty strings count as
+// unset, since WiredTiger rejects e.g. `block_compressor=`.
+impl fmt::Display for CreateConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = Self::default();
+        let mut w = ConfigWriter::default();
+        if self.allocation_size != d.allocation_size {
+            w.push("allocation_size", self.allocation_size);
+        }
+        w.string("app_metadata", &self.app_metadata);
+        if self.block_allocation != d.block_allocation {
+            w.push("block_allocation", self.block_allocation.token());
+        }
+        w.string("block_compressor", &self.block_compressor);
+        if self.cache_resident != d.cache_resident {
+            w.push("cache_resident", self.cache_resident);
+        }
+        if self.checksum != d.checksum {
+            w.push("checksum", self.checksum.token());
+        }
+        if !self.colgroups.is_empty() {
+            w.list("colgroups", self.colgroups.iter().map(|c| quote(c)));
+        }
+        w.string("collator", &self.collator);
+        if !self.columns.is_empty() {
+            w.list("columns", self.columns.iter().map(|c| quote(c)));
+        }
+        if self.dictionary != d.dictionary {
+            w.push("dictionary", self.dictionary);
+        }
+        if self.exclusive != d.exclusive {
+            w.push("exclusive", self.exclusive);
+        }
+        w.string("extractor", &self.extractor);
+        w.string("format", &self.format);
+        w.string("huffman_key", &self.huffman_key);
+        w.string("huffman_value", &self.huffman_value);
+        if self.immutable != d.immutable {
+            w.push("immutable", self.immutable);
+        }
+        if self.internal_key_max != d.internal_key_max {
+            w.push("internal_key_max", size(self.internal_key_max as u64));
+        }
+        if self.internal_key_truncate != d.internal_key_truncate {
+            w.push("internal_key_truncate", self.internal_key_truncate);
+        }
+        if self.internal_page_max != d.internal_page_max {
+            w.push("internal_page_max", size(self.internal_page_max as u64));
+        }
+        w.string("key_format", &self.key_format);
+        if self.leaf_key_max != d.leaf_key_max {
+            w.push("leaf_key_max", size(self.leaf_key_max as u64));
+        }
+        if self.leaf_page_max != d.leaf_page_max {
+            w.push("leaf_page_max", size(self.leaf_page_max as u64));
+        }
+        if self.leaf_value_max != d.leaf_value_max {
+            w.push("leaf_value_max", size(self.leaf_value_max as u64));
+        }
+        w.sub("lsm", |w| self.lsm_config.write_config(w));
+        if self.memory_page_max != d.memory_page_max {
+            w.push("memory_page_max", size(self.memory_page_max as u64));
+        }
+        if self.os_cache_dirty_max != d.os_cache_dirty_max {
+            w.push("os_cache_dirty_max", size(self.os_cache_dirty_max as u64));
+        }
+        if self.os_cache_max != d.os_cache_max {
+            w.push("os_cache_max", size(self.os_cache_max as u64));
+        }
+        if self.prefix_compression != d.prefix_compression {
+            w.push("prefix_compression", self.prefix_compression);
+        }
+        if self.prefix_compression_min != d.prefix_compression_min {
+            w.push("prefix_compression_min", self.prefix_compression_min);
+        }
+        if self.split_pct != d.split_pct {
+            w.push("split_pct", self.split_pct);
+        }
+        w.string("type", &self.data_type);
+        w.string("value_format", &self.value_format);
+        write!(f, "{}", w)
+    }
+}
+
+impl CreateConfig {
+    // WiredTiger requires one column name for each field of the key and value
+    // formats, but its error doesn't say which side is wrong.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.columns.is_empty() {
+            return Ok(());
+        }
+        let format_or_default =
+            |format: &str| if format.is_empty() { "u" } else { format }.to_string();
+        let key_format = format_or_default(&self.key_format);
+        let value_format = format_or_default(&self.value_format);
+        let key_fields = fields(&key_format)?.len();
+        let value_fields = fields(&value_format)?.len();
+        let value_columns = self.columns.len().saturating_sub(key_fields);
+        if self.columns.len() != key_fields + value_fields {
+            return Err(Error::new(format!(
+                "columns ({}) name {} value column(s) after the {} key column(s) of key_format {:?}, \
+                 but value_format {:?} has {} field(s)",
+                self.columns.join(","),
+                value_columns,
+                key_fields,
+                key_format,
+                value_format,
+                value_fields
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Typed configuration for `Session::drop_with`, built with `DropConfig::builder()`.
+pub struct DropConfig {
     // return success if the object does not exist.	Default false.
     force: bool,
 
@@ -510,6 +1497,148 @@ struct DropConfig {
     remove_files: bool,
 }
 
+impl Default for DropConfig {
+    fn default() -> Self {
+        Self {
+            force: false,
+            remove_files: true,
+        }
+    }
+}
+
+impl DropConfig {
+    pub fn builder() -> DropConfigBuilder {
+        DropConfigBuilder {
+            config: Self::default(),
+        }
+    }
+}
+
+/// Builds a `DropConfig`.
+pub struct DropConfigBuilder {
+    config: DropConfig,
+}
+
+impl DropConfigBuilder {
+    /// Succeed even if the object doesn't exist.
+    pub fn force(mut self, force: bool) -> Self {
+        self.config.force = force;
+        self
+    }
+
+    /// Whether to delete the underlying files, rather than just the metadata.
+    pub fn remove_files(mut self, remove_files: bool) -> Self {
+        self.config.remove_files = remove_files;
+        self
+    }
+
+    pub fn build(self) -> DropConfig {
+        self.config
+    }
+}
+
+impl fmt::Display for DropConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = Self::default();
+        let mut w = ConfigWriter::default();
+        if self.force != d.force {
+            w.push("force", self.force);
+        }
+        if self.remove_files != d.remove_files {
+            w.push("remove_files", self.remove_files);
+        }
+        write!(f, "{}", w)
+    }
+}
+
+/// How a cursor joined with `JoinBuilder::add_with` compares with its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+impl Compare {
+    fn token(&self) -> &'static str {
+        match self {
+            Compare::Eq => "eq",
+            Compare::Ge => "ge",
+            Compare::Gt => "gt",
+            Compare::Le => "le",
+            Compare::Lt => "lt",
+        }
+    }
+}
+
+/// How a cursor added to a join combines with the cursors before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinOperation {
+    /// Records must match this cursor as well.
+    And,
+    /// Records may match this cursor instead.
+    Or,
+}
+
+impl JoinOperation {
+    fn token(&self) -> &'static str {
+        match self {
+            JoinOperation::And => "and",
+            JoinOperation::Or => "or",
+        }
+    }
+}
+
+/// Configuration for a cursor added to a join with `JoinBuilder::add_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinConfig {
+    /// Which records of the joined cursor match, relative to its position.
+    /// Default `Eq`.
+    pub compare: Compare,
+    /// An estimate of the number of matching records, used to size the Bloom
+    /// filter. Zero leaves it to WiredTiger.
+    pub count: u64,
+    /// Check candidates against a Bloom filter of the matching records rather
+    /// than by searching the cursor. Default false.
+    pub bloom: bool,
+    /// Whether records must match this cursor and the preceding ones, or either.
+    /// Default `And`.
+    pub operation: JoinOperation,
+}
+
+impl Default for JoinConfig {
+    fn default() -> Self {
+        Self {
+            compare: Compare::Eq,
+            count: 0,
+            bloom: false,
+            operation: JoinOperation::And,
+        }
+    }
+}
+
+impl fmt::Display for JoinConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = Self::default();
+        let mut w = ConfigWriter::default();
+        if self.compare != d.compare {
+            w.push("compare", self.compare.token());
+        }
+        if self.count != d.count {
+            w.push("count", self.count);
+        }
+        if self.operation != d.operation {
+            w.push("operation", self.operation.token());
+        }
+        if self.bloom {
+            w.push("strategy", "bloom");
+        }
+        write!(f, "{}", w)
+    }
+}
+
 struct LSMConfig {
     // Throttle inserts into LSM trees if flushing to disk isn't keeping up.
     // A boolean flag; default true.
@@ -548,7 +1677,7 @@ struct LSMConfig {
     // This is a soft limit, and chunks larger than this value can be created.
     // Must be larger than chunk_size.
     // An integer between 100MB and 10TB; default 5GB.
-    chunk_max: u32,
+    chunk_max: u64,
 
     // The maximum size of the in-memory chunk of an LSM tree.
     // This limit is soft - it is possible for chunks to be temporarily larger than this value.
@@ -565,3 +1694,450 @@ struct LSMConfig {
     // An integer no more than 100; default 0.
     merge_min: u16,
 }
+
+impl Default for LSMConfig {
+    fn default() -> Self {
+        Self {
+            auto_throttle: true,
+            bloom: true,
+            bloom_bit_count: 16,
+            bloom_config: String::new(),
+            bloom_hash_count: 8,
+            bloom_oldest: false,
+            chunk_count_limit: 0,
+            chunk_max: 5 * GB,
+            chunk_size: 10 * MB as u32,
+            merge_max: 15,
+            merge_min: 0,
+        }
+    }
+}
+
+impl LSMConfig {
+    fn write_config(&self, w: &mut ConfigWriter) {
+        let d = Self::default();
+        if self.auto_throttle != d.auto_throttle {
+            w.push("auto_throttle", self.auto_throttle);
+        }
+        if self.bloom != d.bloom {
+            w.push("bloom", self.bloom);
+        }
+        if self.bloom_bit_count != d.bloom_bit_count {
+            w.push("bloom_bit_count", self.bloom_bit_count);
+        }
+        w.string("bloom_config", &self.bloom_config);
+        if self.bloom_hash_count != d.bloom_hash_count {
+            w.push("bloom_hash_count", self.bloom_hash_count);
+        }
+        if self.bloom_oldest != d.bloom_oldest {
+            w.push("bloom_oldest", self.bloom_oldest);
+        }
+        if self.chunk_count_limit != d.chunk_count_limit {
+            w.push("chunk_count_limit", self.chunk_count_limit);
+        }
+        if self.chunk_max != d.chunk_max {
+            w.push("chunk_max", size(self.chunk_max));
+        }
+        if self.chunk_size != d.chunk_size {
+            w.push("chunk_size", size(self.chunk_size as u64));
+        }
+        if self.merge_max != d.merge_max {
+            w.push("merge_max", self.merge_max);
+        }
+        if self.merge_min != d.merge_min {
+            w.push("merge_min", self.merge_min);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_empty() {
+        assert_eq!(OpenConnectionConfig::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_top_level_fields() {
+        let config = OpenConnectionConfig {
+            create: true,
+            cache_size: 500 * MB,
+            statistics: vec![StatisticsOption::Fast],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "cache_size=500MB,create=true,statistics=[fast]"
+        );
+
+        let config = OpenConnectionConfig {
+            buffer_alignment: 4096,
+            cache_size: 2 * GB + 1,
+            checkpoint_sync: false,
+            error_prefix: "my app".to_string(),
+            verbose: vec![VerboseOption::EvictServer, VerboseOption::Read],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "buffer_alignment=4096,cache_size=2147483649,checkpoint_sync=false,\
+             error_prefix=\"my app\",verbose=[evictserver,read]"
+        );
+    }
+
+    #[test]
+    fn test_verbose_tokens() {
+        use VerboseOption::*;
+        let options = [
+            (Api, "api"),
+            (Block, "block"),
+            (Checkpoint, "checkpoint"),
+            (Compact, "compact"),
+            (Evict, "evict"),
+            (EvictServer, "evictserver"),
+            (FileOps, "fileops"),
+            (Log, "log"),
+            (Lsm, "lsm"),
+            (Metadata, "metadata"),
+            (Mutex, "mutex"),
+            (Overflow, "overflow"),
+            (Read, "read"),
+            (Reconcile, "reconcile"),
+            (Recovery, "recovery"),
+            (Salvage, "salvage"),
+            (SharedCache, "shared_cache"),
+            (Split, "split"),
+            (Temporary, "temporary"),
+            (Transaction, "transaction"),
+            (Verify, "verify"),
+            (Version, "version"),
+            (Write, "write"),
+        ];
+        for (option, token) in options {
+            assert_eq!(option.as_config_token(), token);
+        }
+    }
+
+    #[test]
+    fn test_statistics_tokens() {
+        assert_eq!(StatisticsOption::All.as_config_token(), "all");
+        assert_eq!(StatisticsOption::Fast.as_config_token(), "fast");
+        assert_eq!(StatisticsOption::None.as_config_token(), "none");
+        assert_eq!(StatisticsOption::Clear.as_config_token(), "clear");
+    }
+
+    #[test]
+    fn test_direct_io_tokens() {
+        assert_eq!(DirectIOSetting::Checkpoint.as_config_token(), "checkpoint");
+        assert_eq!(DirectIOSetting::Data.as_config_token(), "data");
+        assert_eq!(DirectIOSetting::Log.as_config_token(), "log");
+    }
+
+    #[test]
+    fn test_sync_method_tokens() {
+        assert_eq!(SyncMethodOption::DSync.as_config_token(), "dsync");
+        assert_eq!(SyncMethodOption::FSync.as_config_token(), "fsync");
+        assert_eq!(SyncMethodOption::None.as_config_token(), "none");
+    }
+
+    #[test]
+    fn test_file_extension_tokens() {
+        assert_eq!(FileExtensionConfigOption::Data.as_config_token(), "data");
+        assert_eq!(FileExtensionConfigOption::Log.as_config_token(), "log");
+    }
+
+    #[test]
+    fn test_statistics_log_config() {
+        let config = OpenConnectionConfig::builder()
+            .statistics(&[StatisticsOption::Fast])
+            .statistics_log(30, &["table:", "file:a.wt"])
+            .build();
+        assert_eq!(
+            config.to_string(),
+            r#"statistics=[fast],statistics_log=(sources=["table:","file:a.wt"],wait=30)"#
+        );
+    }
+
+    #[test]
+    fn test_tiered_storage_config() {
+        let config = OpenConnectionConfig::builder()
+            .tiered_storage(TieredStorageConfig {
+                name: "s3_store".to_string(),
+                bucket: "my-bucket;us-east-2".to_string(),
+                local_retention: 60,
+                ..Default::default()
+            })
+            .build();
+        assert_eq!(
+            config.to_string(),
+            "tiered_storage=(bucket=my-bucket;us-east-2,local_retention=60,name=s3_store)"
+        );
+    }
+
+    #[test]
+    fn test_nested_configs() {
+        let config = OpenConnectionConfig {
+            create: true,
+            eviction: EvictionConfig {
+                threads_max: 4,
+                ..Default::default()
+            },
+            checkpoint: CheckpointConfig {
+                wait: 60,
+                log_size: 64 * MB as i32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "checkpoint=(log_size=64MB,wait=60),create=true,eviction=(threads_max=4)"
+        );
+
+        let config = OpenConnectionConfig {
+            log: LogConfig {
+                enabled: true,
+                compressor: "snappy".to_string(),
+                session_max: 200,
+                ..Default::default()
+            },
+            transaction_sync: TransactionSyncConfig {
+                enabled: true,
+                method: SyncMethodOption::DSync,
+            },
+            statistics_log: StatisticsLogConfig {
+                wait: 30,
+                sources: vec!["table:foo".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "log=(compressor=snappy,enabled=true),session_max=200,\
+             statistics_log=(sources=[\"table:foo\"],wait=30),\
+             transaction_sync=(enabled=true,method=dsync)"
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_config() {
+        assert_eq!(CheckpointConfig::default().to_string(), "");
+        let config = CheckpointConfig {
+            wait: 60,
+            log_size: MB as i32,
+            ..Default::default()
+        };
+        assert_eq!(config.to_string(), "checkpoint=(log_size=1MB,wait=60)");
+    }
+
+    #[test]
+    fn test_builder() {
+        let config = OpenConnectionConfig::builder()
+            .create(true)
+            .cache_size_mb(512)
+            .eviction_threads(2, 4)
+            .statistics(&[StatisticsOption::Fast])
+            .build();
+        assert_eq!(
+            config.to_string(),
+            "cache_size=512MB,create=true,eviction=(threads_max=4,threads_min=2),statistics=[fast]"
+        );
+
+        // Setting a field back to its default leaves it out.
+        let config = OpenConnectionConfig::builder()
+            .cache_size_mb(100)
+            .log_enabled(true)
+            .build();
+        assert_eq!(config.to_string(), "log=(enabled=true)");
+
+        let config = OpenConnectionConfig::builder()
+            .buffer_alignment(512)
+            .unwrap()
+            .cache_overhead(12)
+            .unwrap()
+            .build();
+        assert_eq!(config.to_string(), "buffer_alignment=512,cache_overhead=12");
+        assert!(OpenConnectionConfig::builder()
+            .buffer_alignment(-2)
+            .is_err());
+        assert!(OpenConnectionConfig::builder()
+            .buffer_alignment(2 * MB as i32)
+            .is_err());
+        assert!(OpenConnectionConfig::builder().cache_overhead(31).is_err());
+    }
+
+    #[test]
+    fn test_create_config_data_source() {
+        let config = CreateConfig::builder()
+            .data_type("lsm")
+            .unwrap()
+            .format("btree")
+            .unwrap()
+            .build();
+        assert_eq!(config.to_string(), "format=btree,type=lsm");
+        assert!(CreateConfig::builder().data_type("table").is_err());
+        assert!(CreateConfig::builder().format("fixed").is_err());
+    }
+
+    #[test]
+    fn test_create_config() {
+        assert_eq!(CreateConfig::default().to_string(), "");
+
+        let config = CreateConfig::builder()
+            .key_format("S")
+            .value_format("Si")
+            .columns(&["name", "city", "age"])
+            .build();
+        assert_eq!(
+            config.to_string(),
+            "columns=[name,city,age],key_format=S,value_format=Si"
+        );
+
+        let config = CreateConfig {
+            block_compressor: "snappy".to_string(),
+            colgroups: vec!["a".to_string(), "b".to_string()],
+            checksum: ChecksumOption::On,
+            leaf_page_max: 64 * KB as u32,
+            lsm_config: LSMConfig {
+                bloom: false,
+                ..Default::default()
+            },
+            data_type: "lsm".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "block_compressor=snappy,checksum=on,colgroups=[a,b],leaf_page_max=64KB,\
+             lsm=(bloom=false),type=lsm"
+        );
+    }
+
+    #[test]
+    fn test_validate_columns() {
+        let config = |columns: &[&str]| {
+            CreateConfig::builder()
+                .key_format("S")
+                .value_format("Si")
+                .columns(columns)
+                .build()
+        };
+        assert!(config(&["id", "name", "age"]).validate().is_ok());
+        let err = config(&["id", "name"]).validate().unwrap_err();
+        assert_eq!(
+            err.message,
+            "columns (id,name) name 1 value column(s) after the 1 key column(s) of \
+             key_format \"S\", but value_format \"Si\" has 2 field(s)"
+        );
+    }
+
+    #[test]
+    fn test_allocation_size() {
+        let config = CreateConfig::builder()
+            .allocation_size(512)
+            .unwrap()
+            .build();
+        assert_eq!(config.to_string(), "allocation_size=512");
+        let config = CreateConfig::builder()
+            .allocation_size(64 * KB as u32)
+            .unwrap()
+            .build();
+        assert_eq!(config.to_string(), "allocation_size=65536");
+
+        for bytes in [0, 256, 3000, 256 * MB as u32] {
+            assert!(CreateConfig::builder().allocation_size(bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_drop_config() {
+        assert_eq!(DropConfig::default().to_string(), "");
+        let config = DropConfig::builder()
+            .force(true)
+            .remove_files(false)
+            .build();
+        assert_eq!(config.to_string(), "force=true,remove_files=false");
+    }
+
+    #[test]
+    fn test_open_session_config() {
+        assert_eq!(OpenSessionConfig::default().to_string(), "");
+        let config = OpenSessionConfig {
+            isolation: Some(IsolationLevel::ReadCommitted),
+            cache_cursors: false,
+        };
+        assert_eq!(
+            config.to_string(),
+            "cache_cursors=false,isolation=read-committed"
+        );
+    }
+
+    #[test]
+    fn test_join_config() {
+        assert_eq!(JoinConfig::default().to_string(), "");
+        let config = JoinConfig {
+            compare: Compare::Ge,
+            count: 1000,
+            bloom: true,
+            operation: JoinOperation::Or,
+        };
+        assert_eq!(
+            config.to_string(),
+            "compare=ge,count=1000,operation=or,strategy=bloom"
+        );
+    }
+
+    #[test]
+    fn test_workload_presets() {
+        let preset = |preset| {
+            CreateConfig::builder()
+                .workload_preset(preset)
+                .build()
+                .to_string()
+        };
+        assert_eq!(
+            preset(Preset::ReadHeavy),
+            "internal_page_max=16KB,leaf_page_max=16KB,memory_page_max=2MB"
+        );
+        assert_eq!(
+            preset(Preset::WriteHeavy),
+            "internal_page_max=16KB,leaf_page_max=64KB,memory_page_max=20MB"
+        );
+        assert_eq!(
+            preset(Preset::LargeValues),
+            "allocation_size=16384,internal_page_max=64KB,leaf_page_max=128KB,memory_page_max=50MB"
+        );
+        assert_eq!(
+            preset(Preset::SmallKeys),
+            "leaf_page_max=8KB,memory_page_max=1MB"
+        );
+
+        // Later settings still apply on top of a preset.
+        let config = CreateConfig::builder()
+            .workload_preset(Preset::SmallKeys)
+            .key_format("q")
+            .build();
+        assert_eq!(
+            config.to_string(),
+            "key_format=q,leaf_page_max=8KB,memory_page_max=1MB"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let config = OpenConnectionConfig {
+            direct_io: vec![DirectIOSetting::Data, DirectIOSetting::Log],
+            extensions: vec!["/usr/lib/libwiredtiger_snappy.so".to_string()],
+            file_extend: vec![(FileExtensionConfigOption::Data, 16 * MB)],
+            statistics: vec![StatisticsOption::All, StatisticsOption::Clear],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_string(),
+            "direct_io=[data,log],extensions=[/usr/lib/libwiredtiger_snappy.so],\
+             file_extend=(data=16MB),statistics=[all,clear]"
+        );
+    }
+}
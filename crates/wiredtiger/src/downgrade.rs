@@ -0,0 +1,68 @@
+use crate::raw_api::{self, Error, Result};
+use crate::Connection;
+
+// Parses a WiredTiger release, `major.minor` or `major.minor.patch`.
+fn parse_release(release: &str) -> Option<(i32, i32, i32)> {
+    let mut parts = release.split('.').map(|part| part.parse::<i32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    parts.next().is_none().then_some((major, minor, patch))
+}
+
+impl Connection {
+    /// Switches the database to the on-disk format of the older WiredTiger
+    /// `release` (e.g. `10.0`) and checkpoints, so that release can open it.
+    /// Fails if `release` isn't older than the linked library.
+    pub fn downgrade(&self, release: &str) -> Result<()> {
+        let target = parse_release(release)
+            .ok_or_else(|| Error::new(format!("invalid release: {:?}", release)))?;
+        let current = raw_api::version();
+        if target >= current {
+            return Err(Error::new(format!(
+                "can only downgrade to a release older than {}.{}.{}, not {}",
+                current.0, current.1, current.2, release
+            )));
+        }
+        self.reconfigure(&format!("compatibility=(release={})", release))?;
+        self.open_session()?.checkpoint("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_release;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_parse_release() {
+        assert_eq!(parse_release("10.0"), Some((10, 0, 0)));
+        assert_eq!(parse_release("3.3.1"), Some((3, 3, 1)));
+        assert_eq!(parse_release("10"), None);
+        assert_eq!(parse_release("10.x"), None);
+        assert_eq!(parse_release("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        {
+            let conn = assert_ok!(Connection::open(home, "create,log=(enabled=true)"));
+            let sess = assert_ok!(conn.open_session());
+            assert_ok!(sess.create("table:old", "key_format=S,value_format=S"));
+            assert!(conn.downgrade("99.0").is_err());
+            assert!(conn.downgrade("ten").is_err());
+            assert_ok!(conn.downgrade("10.0"));
+        }
+
+        // Opening requires the files to be in a format 10.0 can read.
+        let conn = assert_ok!(Connection::open(
+            home,
+            "log=(enabled=true),compatibility=(require_max=10.0.0)"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.open_cursor("table:old"));
+    }
+}
@@ -0,0 +1,392 @@
+use crate::raw_api::{self, Error, Result};
+use crate::Session;
+use std::io::{BufRead, ErrorKind, Read, Write};
+
+// The `wt dump -x` / `wt load` text format:
+//
+//   WiredTiger Dump (WiredTiger Version 11.2.0)
+//   Format=hex
+//   Header
+//   <uri>
+//   <config>
+//   <colgroup uri>
+//   <colgroup config>
+//   ...
+//   Data
+//   <hex key>
+//   <hex value>
+//   ...
+const DUMP_BANNER: &str = "WiredTiger Dump";
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(line: &str) -> Result<Vec<u8>> {
+    if !line.len().is_multiple_of(2) {
+        return Err(Error::new(format!("invalid hex in dump: {:?}", line)));
+    }
+    (0..line.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&line[i..i + 2], 16)
+                .map_err(|_| Error::new(format!("invalid hex in dump: {:?}", line)))
+        })
+        .collect()
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::new(format!("I/O error during dump: {}", e))
+}
+
+// The binary format of `export_table`: this magic number, then the table's
+// config, then each record's key and value, each of these prefixed with its
+// length as a little-endian u32.
+const EXPORT_MAGIC: &[u8] = b"WTEXPORT1";
+
+fn write_chunk<W: Write>(w: &mut W, bytes: &[u8]) -> Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| Error::new(format!("{} bytes is too long to export", bytes.len())))?;
+    w.write_all(&len.to_le_bytes()).map_err(io_error)?;
+    w.write_all(bytes).map_err(io_error)
+}
+
+// Reads a length-prefixed chunk, or `None` at the end of the input if `optional`.
+fn read_chunk<R: Read>(r: &mut R, optional: bool) -> Result<Option<Vec<u8>>> {
+    let truncated = || Error::new("truncated export");
+    let mut len = [0; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match r.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 && optional => return Ok(None),
+            Ok(0) => return Err(truncated()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    r.read_exact(&mut bytes).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => truncated(),
+        _ => io_error(e),
+    })?;
+    Ok(Some(bytes))
+}
+
+impl Session {
+    /// Writes the contents of `uri` in the same hex format as the `wt dump -x`
+    /// utility, so the output can be read back with `wt load` or `load_wt`.
+    /// Returns the number of records written.
+    pub fn dump_wt<W: Write>(&self, uri: &str, w: &mut W) -> Result<u64> {
+        let metadata = self.open_cursor("metadata:")?;
+        metadata.set_key(uri)?;
+        metadata.search()?;
+        let (_, config) = metadata.get_raw_key_value()?;
+        let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+
+        let (major, minor, patch) = raw_api::version();
+        writeln!(
+            w,
+            "{} (WiredTiger Version {}.{}.{})",
+            DUMP_BANNER, major, minor, patch
+        )
+        .map_err(io_error)?;
+        writeln!(w, "Format=hex").map_err(io_error)?;
+        writeln!(w, "Header").map_err(io_error)?;
+        writeln!(w, "{}", uri).map_err(io_error)?;
+        writeln!(w, "{}", config).map_err(io_error)?;
+        // As `wt dump` does, follow a table with its column groups, which `wt
+        // load` needs to recreate a table that has several.
+        if let Some(table) = uri.strip_prefix("table:") {
+            let colgroup = format!("colgroup:{}", table);
+            for (object, config) in self.list_by_type(&colgroup)? {
+                // Skip other tables whose names start with this one's.
+                if object == colgroup || object.starts_with(&format!("{}:", colgroup)) {
+                    writeln!(w, "{}", object).map_err(io_error)?;
+                    writeln!(w, "{}", config).map_err(io_error)?;
+                }
+            }
+        }
+        writeln!(w, "Data").map_err(io_error)?;
+
+        let cursor = self.open_cursor_with_config(uri, "raw")?;
+        let mut count = 0;
+        loop {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+            let (key, value) = cursor.raw_cursor.get_packed_key_value()?;
+            writeln!(w, "{}", to_hex(&key)).map_err(io_error)?;
+            writeln!(w, "{}", to_hex(&value)).map_err(io_error)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Reads a dump produced by `dump_wt` (or `wt dump -x`), creates the objects
+    /// listed in its header and inserts its records. Returns the number of
+    /// records loaded.
+    pub fn load_wt<R: BufRead>(&self, r: &mut R) -> Result<u64> {
+        let mut lines = r.lines();
+        let mut next_line = |what: &str| -> Result<Option<String>> {
+            match lines.next() {
+                Some(line) => line.map(Some).map_err(io_error),
+                None if what.is_empty() => Ok(None),
+                None => Err(Error::new(format!("truncated dump: expected {}", what))),
+            }
+        };
+
+        let banner = next_line("banner")?.unwrap_or_default();
+        if !banner.starts_with(DUMP_BANNER) {
+            return Err(Error::new("not a WiredTiger dump"));
+        }
+        let format = next_line("format")?.unwrap_or_default();
+        if format != "Format=hex" {
+            return Err(Error::new(format!("unsupported dump format: {:?}", format)));
+        }
+        if next_line("header")?.as_deref() != Some("Header") {
+            return Err(Error::new("malformed dump: missing Header"));
+        }
+
+        let mut uri = None;
+        loop {
+            let object = next_line("object uri")?.unwrap_or_default();
+            if object == "Data" {
+                break;
+            }
+            let config = next_line("object config")?.unwrap_or_default();
+            self.create(&object, &config)?;
+            uri.get_or_insert(object);
+        }
+        let uri = uri.ok_or_else(|| Error::new("malformed dump: no objects in header"))?;
+
+        let cursor = self.open_cursor_with_config(&uri, "raw")?;
+        let mut count = 0;
+        while let Some(key) = next_line("")? {
+            let value = next_line("value")?.unwrap_or_default();
+            cursor.raw_cursor.set_key_item(&from_hex(&key)?)?;
+            cursor.raw_cursor.set_value_item(&from_hex(&value)?)?;
+            cursor.insert()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Writes `uri`'s config and records, as length-prefixed binary, for
+    /// `import_table`. Unlike `dump_wt` nothing is hex-encoded, so it's compact
+    /// and binary keys and values are copied exactly.
+    pub fn export_table<W: Write>(&self, uri: &str, w: &mut W) -> Result<()> {
+        let metadata = self.open_cursor("metadata:")?;
+        metadata.set_key(uri)?;
+        metadata.search()?;
+        let (_, config) = metadata.raw_cursor.get_packed_key_value()?;
+        // The packed config string carries its NUL terminator.
+        let config = config.strip_suffix(b"\0").unwrap_or(&config);
+
+        w.write_all(EXPORT_MAGIC).map_err(io_error)?;
+        write_chunk(w, config)?;
+        let cursor = self.open_cursor_with_config(uri, "raw")?;
+        loop {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+            let (key, value) = cursor.raw_cursor.get_packed_key_value()?;
+            write_chunk(w, &key)?;
+            write_chunk(w, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Creates `uri` from an `export_table` export, which needn't have been of a
+    /// table of the same name, and inserts its records. `uri` mustn't exist
+    /// already. If the export can't be read to the end the new table is dropped
+    /// again, leaving no partial copy.
+    pub fn import_table<R: Read>(&self, uri: &str, r: &mut R) -> Result<()> {
+        let mut magic = vec![0; EXPORT_MAGIC.len()];
+        if r.read_exact(&mut magic).is_err() || magic != EXPORT_MAGIC {
+            return Err(Error::new("not a table export"));
+        }
+        let config = read_chunk(r, false)?.unwrap_or_default();
+        // Exclusive, so a failed import never drops a table it didn't create.
+        let mut config = String::from_utf8_lossy(&config).into_owned();
+        if !config.is_empty() {
+            config.push(',');
+        }
+        config.push_str("exclusive=true");
+        self.create(uri, &config)?;
+        let result = self.import_records(uri, r);
+        if result.is_err() {
+            // The import's error is the one worth reporting.
+            let _ = self.drop(uri, "");
+        }
+        result
+    }
+
+    fn import_records<R: Read>(&self, uri: &str, r: &mut R) -> Result<()> {
+        let cursor = self.open_cursor_with_config(uri, "raw")?;
+        while let Some(key) = read_chunk(r, true)? {
+            let value = read_chunk(r, false)?.unwrap_or_default();
+            cursor.raw_cursor.set_key_item(&key)?;
+            cursor.raw_cursor.set_value_item(&value)?;
+            cursor.insert()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_dump_load_round_trip() {
+        let rows = [
+            ("apple", "red"),
+            ("banana", "yellow"),
+            ("cherry", "dark red"),
+        ];
+
+        let src_dir = tempfile::tempdir().unwrap();
+        let src = assert_ok!(Connection::open(src_dir.path().to_str().unwrap(), "create"));
+        let src_sess = assert_ok!(src.open_session());
+        assert_ok!(src_sess.create("table:fruit", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(src_sess.open_cursor("table:fruit"));
+            for (k, v) in rows {
+                assert_ok!(cur.set_key(k));
+                assert_ok!(cur.set_value(v));
+                assert_ok!(cur.insert());
+            }
+        }
+
+        let mut dump = Vec::new();
+        assert_eq!(assert_ok!(src_sess.dump_wt("table:fruit", &mut dump)), 3);
+        let text = String::from_utf8(dump.clone()).unwrap();
+        assert!(text.starts_with("WiredTiger Dump (WiredTiger Version "));
+        assert!(text.contains("\nFormat=hex\nHeader\ntable:fruit\n"));
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let dst = assert_ok!(Connection::open(dst_dir.path().to_str().unwrap(), "create"));
+        let dst_sess = assert_ok!(dst.open_session());
+        assert_eq!(assert_ok!(dst_sess.load_wt(&mut dump.as_slice())), 3);
+
+        let cur = assert_ok!(dst_sess.open_cursor("table:fruit"));
+        for (k, v) in rows {
+            assert_ok!(cur.next());
+            let (key, value) = assert_ok!(cur.get_raw_key_value());
+            assert_eq!(assert_ok!(std::str::from_utf8(&key.unwrap())), k);
+            assert_eq!(assert_ok!(std::str::from_utf8(&value.unwrap())), v);
+        }
+        assert!(cur.next().unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_dump_load_column_groups() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let src = assert_ok!(Connection::open(src_dir.path().to_str().unwrap(), "create"));
+        let src_sess = assert_ok!(src.open_session());
+        assert_ok!(src_sess.create(
+            "table:split",
+            "key_format=S,value_format=SS,columns=(k,a,b),colgroups=(first,second)"
+        ));
+        assert_ok!(src_sess.create("colgroup:split:first", "columns=(a)"));
+        assert_ok!(src_sess.create("colgroup:split:second", "columns=(b)"));
+        // Another table whose name starts with this one's stays out of the dump.
+        assert_ok!(src_sess.create("table:splitter", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(src_sess.open_cursor_with_config("table:split", "raw"));
+            assert_ok!(cur.raw_cursor.set_key_item(b"k\0"));
+            assert_ok!(cur.raw_cursor.set_value_item(b"a\0b\0"));
+            assert_ok!(cur.insert());
+        }
+
+        let mut dump = Vec::new();
+        assert_eq!(assert_ok!(src_sess.dump_wt("table:split", &mut dump)), 1);
+        let text = String::from_utf8(dump.clone()).unwrap();
+        assert!(text.contains("\ncolgroup:split:first\n"));
+        assert!(text.contains("\ncolgroup:split:second\n"));
+        assert!(!text.contains("splitter"));
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let dst = assert_ok!(Connection::open(dst_dir.path().to_str().unwrap(), "create"));
+        let dst_sess = assert_ok!(dst.open_session());
+        assert_eq!(assert_ok!(dst_sess.load_wt(&mut dump.as_slice())), 1);
+        let cur = assert_ok!(dst_sess.open_cursor("table:split(b)"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.raw_cursor.get_packed_key_value()).1,
+            b"b\0".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let src = assert_ok!(Connection::open(src_dir.path().to_str().unwrap(), "create"));
+        let src_sess = assert_ok!(src.open_session());
+        assert_ok!(src_sess.create("table:blobs", "key_format=u,value_format=u"));
+        // Keys and values that aren't text, including NULs and empty values.
+        let rows: [(&[u8], &[u8]); 3] = [
+            (b"\x00\x01", b"\xff\x00\xfe"),
+            (b"\x00\x02", b""),
+            (b"\x80", b"\n\r"),
+        ];
+        {
+            let cur = assert_ok!(src_sess.open_cursor("table:blobs"));
+            assert_ok!(cur.batch_insert(&rows));
+        }
+
+        let mut export = Vec::new();
+        assert_ok!(src_sess.export_table("table:blobs", &mut export));
+
+        let dst_dir = tempfile::tempdir().unwrap();
+        let dst = assert_ok!(Connection::open(dst_dir.path().to_str().unwrap(), "create"));
+        let dst_sess = assert_ok!(dst.open_session());
+        assert_ok!(dst_sess.import_table("table:copy", &mut export.as_slice()));
+        let cur = assert_ok!(dst_sess.open_cursor("table:copy"));
+        assert_eq!(cur.key_format(), "u");
+        for (k, v) in rows {
+            assert_ok!(cur.next());
+            let (key, value) = assert_ok!(cur.raw_cursor.get_packed_key_value());
+            assert_eq!(key, k);
+            assert_eq!(value, v);
+        }
+        assert!(cur.next().unwrap_err().is_not_found());
+
+        // A cut-off export is an error rather than a partial table.
+        let truncated = &export[..export.len() - 1];
+        assert!(dst_sess
+            .import_table("table:truncated", &mut &truncated[..])
+            .is_err());
+        assert!(dst_sess.open_cursor("table:truncated").is_err());
+        assert!(dst_sess
+            .import_table("table:garbage", &mut "garbage".as_bytes())
+            .is_err());
+
+        // Importing over an existing table fails and leaves its rows alone.
+        assert!(dst_sess
+            .import_table("table:copy", &mut &truncated[..])
+            .is_err());
+        let cur = assert_ok!(dst_sess.open_cursor("table:copy"));
+        for (k, _) in rows {
+            assert_ok!(cur.next());
+            let (key, _) = assert_ok!(cur.raw_cursor.get_packed_key_value());
+            assert_eq!(key, k);
+        }
+        assert!(cur.next().unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_load_rejects_garbage() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(dir.path().to_str().unwrap(), "create"));
+        let sess = assert_ok!(conn.open_session());
+        assert!(sess.load_wt(&mut "not a dump\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::raw_api::{RawConnection, Result};
+use crate::Connection;
+use libc::{c_char, c_int};
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use wiredtiger_sys as wtffi;
+
+/// Receives WiredTiger's diagnostic callbacks. Without a handler WiredTiger
+/// writes errors and messages to stderr/stdout itself.
+pub trait EventHandler: Send + Sync {
+    /// An error occurred, e.g. a failed API call. `code` is the error number.
+    fn on_error(&self, code: i32, message: &str);
+
+    /// An informational or verbose message.
+    fn on_message(&self, message: &str);
+
+    /// Progress of a long-running operation such as salvage or verify, as a
+    /// count of items processed so far.
+    fn on_progress(&self, operation: &str, progress: u64);
+}
+
+/// An `EventHandler` that forwards errors to `log::error!` and everything else
+/// to `log::info!`, under the `wiredtiger` target.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogEventHandler;
+
+impl EventHandler for LogEventHandler {
+    fn on_error(&self, code: i32, message: &str) {
+        log::error!(target: "wiredtiger", "{} ({})", message, code);
+    }
+
+    fn on_message(&self, message: &str) {
+        log::info!(target: "wiredtiger", "{}", message);
+    }
+
+    fn on_progress(&self, operation: &str, progress: u64) {
+        log::info!(target: "wiredtiger", "{}: {}", operation, progress);
+    }
+}
+
+// As with collators and compressors, the WT_EVENT_HANDLER comes first so the
+// pointer WiredTiger passes back can be cast to the whole struct.
+#[repr(C)]
+pub(crate) struct RustEventHandler<'h> {
+    pub(crate) handler: wtffi::WT_EVENT_HANDLER,
+    inner: Box<dyn EventHandler + 'h>,
+}
+
+impl<'h> RustEventHandler<'h> {
+    pub(crate) fn new(inner: Box<dyn EventHandler + 'h>) -> Box<Self> {
+        Box::new(Self {
+            handler: wtffi::WT_EVENT_HANDLER {
+                handle_error: Some(handle_error),
+                handle_message: Some(handle_message),
+                handle_progress: Some(handle_progress),
+                handle_close: None,
+                handle_general: None,
+            },
+            inner,
+        })
+    }
+
+    pub(crate) fn inner(&self) -> &dyn EventHandler {
+        &*self.inner
+    }
+}
+
+unsafe fn dispatch<F: FnOnce(&dyn EventHandler)>(handler: *mut wtffi::WT_EVENT_HANDLER, f: F) {
+    let this = &*(handler as *const RustEventHandler<'_>);
+    // There's nowhere to report a panic to, but it mustn't unwind into C.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| f(&*this.inner)));
+}
+
+unsafe fn text<'a>(s: *const c_char) -> std::borrow::Cow<'a, str> {
+    if s.is_null() {
+        "".into()
+    } else {
+        CStr::from_ptr(s).to_string_lossy()
+    }
+}
+
+unsafe extern "C" fn handle_error(
+    handler: *mut wtffi::WT_EVENT_HANDLER,
+    _session: *mut wtffi::WT_SESSION,
+    error: c_int,
+    message: *const c_char,
+) -> c_int {
+    dispatch(handler, |h| h.on_error(error, &text(message)));
+    0
+}
+
+unsafe extern "C" fn handle_message(
+    handler: *mut wtffi::WT_EVENT_HANDLER,
+    _session: *mut wtffi::WT_SESSION,
+    message: *const c_char,
+) -> c_int {
+    dispatch(handler, |h| h.on_message(&text(message)));
+    0
+}
+
+unsafe extern "C" fn handle_progress(
+    handler: *mut wtffi::WT_EVENT_HANDLER,
+    _session: *mut wtffi::WT_SESSION,
+    operation: *const c_char,
+    progress: u64,
+) -> c_int {
+    dispatch(handler, |h| h.on_progress(&text(operation), progress));
+    0
+}
+
+impl Connection {
+    /// Like `open`, but routes WiredTiger's errors, messages and progress reports
+    /// to `handler` for the life of the connection.
+    pub fn open_with_handler(
+        filename: &str,
+        options: &str,
+        handler: Box<dyn EventHandler>,
+    ) -> Result<Self> {
+        let mut event_handler = RustEventHandler::new(handler);
+        let raw_conn =
+            RawConnection::open_with_event_handler(filename, options, &mut event_handler.handler)?;
+        Ok(Self::from_raw(raw_conn, options, Some(event_handler)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventHandler;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        errors: Mutex<Vec<(i32, String)>>,
+        messages: Mutex<Vec<String>>,
+    }
+
+    struct Recording(Arc<Recorder>);
+
+    impl EventHandler for Recording {
+        fn on_error(&self, code: i32, message: &str) {
+            self.0
+                .errors
+                .lock()
+                .unwrap()
+                .push((code, message.to_string()));
+        }
+
+        fn on_message(&self, message: &str) {
+            self.0.messages.lock().unwrap().push(message.to_string());
+        }
+
+        fn on_progress(&self, _operation: &str, _progress: u64) {}
+    }
+
+    #[test]
+    fn test_event_handler() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let conn = assert_ok!(Connection::open_with_handler(
+            temp_dir.path().to_str().unwrap(),
+            "create,verbose=[checkpoint]",
+            Box::new(Recording(recorder.clone()))
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:events", "key_format=S,value_format=S"));
+        assert_ok!(sess.checkpoint(""));
+        assert!(!recorder.messages.lock().unwrap().is_empty());
+
+        // Failed calls are reported through on_error too.
+        assert!(sess
+            .create("table:bad", "key_format=S,value_format=S,no_such_key=1")
+            .is_err());
+        assert!(!recorder.errors.lock().unwrap().is_empty());
+    }
+}
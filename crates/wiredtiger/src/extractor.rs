@@ -0,0 +1,175 @@
+use crate::raw_api::Result;
+use crate::Connection;
+use libc::c_int;
+use std::panic::{self, AssertUnwindSafe};
+use wiredtiger_sys as wtffi;
+
+/// One key of an index, packed in the index's `key_format` (see
+/// `format::pack`).
+pub type IndexKey = Vec<u8>;
+
+/// Derives an index's keys from a table's records, for indices created with
+/// `extractor=<name>`. Unlike an index on columns, a record can have any number
+/// of index keys, e.g. one per word of a text column.
+pub trait Extractor: Send + Sync {
+    /// The index keys for a record, given its key and value packed in the
+    /// table's formats.
+    fn extract(&self, key: &[u8], value: &[u8]) -> Vec<IndexKey>;
+}
+
+// Laid out so a `*mut WT_EXTRACTOR` handed back by WiredTiger can be cast to the
+// whole struct; see `RustCollator`.
+#[repr(C)]
+struct RustExtractor {
+    extractor: wtffi::WT_EXTRACTOR,
+    inner: Box<dyn Extractor>,
+}
+
+unsafe fn item_bytes<'a>(item: *const wtffi::WT_ITEM) -> &'a [u8] {
+    if item.is_null() || (*item).data.is_null() || (*item).size == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts((*item).data as *const u8, (*item).size)
+    }
+}
+
+unsafe extern "C" fn extract(
+    this: *mut wtffi::WT_EXTRACTOR,
+    _session: *mut wtffi::WT_SESSION,
+    key: *const wtffi::WT_ITEM,
+    value: *const wtffi::WT_ITEM,
+    result_cursor: *mut wtffi::WT_CURSOR,
+) -> c_int {
+    let this = &*(this as *const RustExtractor);
+    let (key, value) = (item_bytes(key), item_bytes(value));
+    let Ok(index_keys) = panic::catch_unwind(AssertUnwindSafe(|| this.inner.extract(key, value)))
+    else {
+        return wtffi::WT_ERROR;
+    };
+    // The keys are already packed, so they're set the way a raw cursor's are.
+    let flags = (*result_cursor).flags;
+    (*result_cursor).flags |= wtffi::WT_CURSTD_RAW as u64;
+    let mut err_code = 0;
+    for index_key in &index_keys {
+        let item = wtffi::WT_ITEM {
+            data: index_key.as_ptr() as *const _,
+            size: index_key.len(),
+            mem: std::ptr::null_mut(),
+            memsize: 0,
+            flags: 0,
+        };
+        (*result_cursor).set_key.unwrap()(result_cursor, &item as *const wtffi::WT_ITEM);
+        err_code = (*result_cursor).insert.unwrap()(result_cursor);
+        if err_code != 0 {
+            break;
+        }
+    }
+    (*result_cursor).flags = flags;
+    err_code
+}
+
+unsafe extern "C" fn terminate(
+    this: *mut wtffi::WT_EXTRACTOR,
+    _session: *mut wtffi::WT_SESSION,
+) -> c_int {
+    drop(Box::from_raw(this as *mut RustExtractor));
+    0
+}
+
+impl Connection {
+    /// Registers `extractor` under `name`, so indices created with
+    /// `extractor=<name>` (and `columns=()`) get their keys from it. It's dropped
+    /// when the connection closes.
+    pub fn add_extractor(&self, name: &str, extractor: Box<dyn Extractor>) -> Result<()> {
+        let raw = Box::into_raw(Box::new(RustExtractor {
+            extractor: wtffi::WT_EXTRACTOR {
+                extract: Some(extract),
+                customize: None,
+                terminate: Some(terminate),
+            },
+            inner: extractor,
+        }));
+        let result = self
+            .raw_conn()
+            .add_extractor(name, raw as *mut wtffi::WT_EXTRACTOR);
+        if result.is_err() {
+            drop(unsafe { Box::from_raw(raw) });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Extractor, IndexKey};
+    use crate::format::{pack, unpack, WtValue};
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    // Indexes a string value by each of its words.
+    struct Words;
+
+    impl Extractor for Words {
+        fn extract(&self, _key: &[u8], value: &[u8]) -> Vec<IndexKey> {
+            let Ok(fields) = unpack("S", value) else {
+                return Vec::new();
+            };
+            let Some(WtValue::Str(text)) = fields.into_iter().next() else {
+                return Vec::new();
+            };
+            text.split_whitespace()
+                .map(|word| pack("S", &[WtValue::Str(word.to_string())]).unwrap())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_add_extractor() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        assert_ok!(conn.add_extractor("words", Box::new(Words)));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:docs", "key_format=S,value_format=S"));
+        assert_ok!(sess.create(
+            "index:docs:words",
+            "key_format=S,columns=(),extractor=words"
+        ));
+        let cur = assert_ok!(sess.open_cursor("table:docs"));
+        for (key, text) in [
+            ("a", "the quick brown fox"),
+            ("b", "the lazy dog"),
+            ("c", "a lazy fox"),
+        ] {
+            assert_ok!(cur.set_key(key));
+            assert_ok!(cur.set_value(text));
+            assert_ok!(cur.insert());
+        }
+
+        // Each word leads to every document containing it.
+        let index = assert_ok!(sess.open_cursor("index:docs:words"));
+        let matching = |word: &str| {
+            let mut texts = Vec::new();
+            assert_ok!(index.set_key(word));
+            if index.search().is_err() {
+                return texts;
+            }
+            loop {
+                let (key, text): (String, String) = assert_ok!(index.get_key_value());
+                if key != word {
+                    break;
+                }
+                texts.push(text);
+                if index.next().is_err() {
+                    break;
+                }
+            }
+            texts
+        };
+        assert_eq!(matching("fox"), ["the quick brown fox", "a lazy fox"]);
+        assert_eq!(matching("dog"), ["the lazy dog"]);
+        assert!(matching("cat").is_empty());
+    }
+}
@@ -0,0 +1,509 @@
+//! Packing and unpacking of WiredTiger's format strings (`key_format` and
+//! `value_format`), without going through a cursor.
+
+use crate::raw_api::{Error, ErrorKind, Result};
+
+/// A single field of a WiredTiger key or value, as described by its format
+/// character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WtValue {
+    /// `b`, `h`, `i`, `l` and `q`.
+    Int(i64),
+    /// `B`, `H`, `I`, `L`, `Q`, `r` and `t`.
+    UInt(u64),
+    /// `s` and `S`.
+    Str(String),
+    /// `u`.
+    Bytes(Vec<u8>),
+}
+
+/// Conversion from the fields of a key or value, for
+/// `Cursor::get_key_value`. Tuples take one field per element.
+pub trait FromWt: Sized {
+    /// Builds `Self` from the fields at the front of `fields`.
+    fn from_wt(fields: &mut std::vec::IntoIter<WtValue>) -> Result<Self>;
+}
+
+fn next_field(fields: &mut std::vec::IntoIter<WtValue>) -> Result<WtValue> {
+    fields.next().ok_or_else(|| {
+        Error::with_kind(
+            ErrorKind::FormatMismatch,
+            "format mismatch: too few fields for the requested type",
+        )
+    })
+}
+
+fn wrong_type(expected: &str, value: WtValue) -> Error {
+    Error::with_kind(
+        ErrorKind::FormatMismatch,
+        format!(
+            "format mismatch: expected {} but found {:?}",
+            expected, value
+        ),
+    )
+}
+
+macro_rules! from_wt {
+    ($type:ty, $variant:ident) => {
+        impl FromWt for $type {
+            fn from_wt(fields: &mut std::vec::IntoIter<WtValue>) -> Result<Self> {
+                match next_field(fields)? {
+                    WtValue::$variant(value) => Ok(value),
+                    value => Err(wrong_type(stringify!($type), value)),
+                }
+            }
+        }
+    };
+}
+
+from_wt!(i64, Int);
+from_wt!(u64, UInt);
+from_wt!(String, Str);
+from_wt!(Vec<u8>, Bytes);
+
+macro_rules! from_wt_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: FromWt),+> FromWt for ($($name,)+) {
+            fn from_wt(fields: &mut std::vec::IntoIter<WtValue>) -> Result<Self> {
+                Ok(($($name::from_wt(fields)?,)+))
+            }
+        }
+    };
+}
+
+from_wt_tuple!(A);
+from_wt_tuple!(A, B);
+from_wt_tuple!(A, B, C);
+from_wt_tuple!(A, B, C, D);
+
+// Converts every one of `fields` to `T`, with none left over.
+pub(crate) fn from_fields<T: FromWt>(fields: Vec<WtValue>) -> Result<T> {
+    let mut fields = fields.into_iter();
+    let value = T::from_wt(&mut fields)?;
+    if fields.len() != 0 {
+        return Err(Error::with_kind(
+            ErrorKind::FormatMismatch,
+            format!(
+                "format mismatch: {} field(s) left over for the requested type",
+                fields.len()
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+// The items of a format string: each field's type along with its size for `s`,
+// `S`, `u` and `t`, and pad bytes as `x` with their count. A count before any
+// other type repeats it.
+fn items(format: &str) -> Result<Vec<(char, Option<usize>)>> {
+    let mut items = Vec::new();
+    let mut count: Option<usize> = None;
+    for c in format.trim_start_matches(['@', '<', '>', '!', '=']).chars() {
+        if let Some(digit) = c.to_digit(10) {
+            count = Some(count.unwrap_or(0) * 10 + digit as usize);
+            continue;
+        }
+        match c {
+            's' | 'S' | 'u' | 't' | 'x' => items.push((c, count)),
+            'b' | 'B' | 'h' | 'H' | 'i' | 'I' | 'l' | 'L' | 'q' | 'Q' | 'r' => {
+                items.extend(std::iter::repeat_n((c, None), count.unwrap_or(1)))
+            }
+            _ => {
+                return Err(Error::new(format!(
+                    "invalid format {:?}: unknown type {:?}",
+                    format, c
+                )))
+            }
+        }
+        count = None;
+    }
+    Ok(items)
+}
+
+// The type of every field in `format`, in order, e.g. ['S', 'i'] for "Si" and
+// ['s', 'q', 'q'] for ">10s2q".
+pub(crate) fn fields(format: &str) -> Result<Vec<char>> {
+    Ok(items(format)?
+        .into_iter()
+        .map(|(c, _)| c)
+        .filter(|c| *c != 'x')
+        .collect())
+}
+
+// Integers are packed so that their encodings sort in numeric order: small
+// values take one byte, and a marker in the first byte's high bits gives the
+// sign and length of the rest.
+const NEG_MULTI_MARKER: u8 = 0x10;
+const NEG_2BYTE_MARKER: u8 = 0x20;
+const NEG_1BYTE_MARKER: u8 = 0x40;
+const POS_1BYTE_MARKER: u8 = 0x80;
+const POS_2BYTE_MARKER: u8 = 0xc0;
+const POS_MULTI_MARKER: u8 = 0xe0;
+
+const NEG_1BYTE_MIN: i64 = -(1 << 6);
+const NEG_2BYTE_MIN: i64 = -(1 << 13) + NEG_1BYTE_MIN;
+const POS_1BYTE_MAX: u64 = (1 << 6) - 1;
+const POS_2BYTE_MAX: u64 = (1 << 13) + POS_1BYTE_MAX;
+
+fn pack_uint(buf: &mut Vec<u8>, x: u64) {
+    if x <= POS_1BYTE_MAX {
+        buf.push(POS_1BYTE_MARKER | x as u8);
+    } else if x <= POS_2BYTE_MAX {
+        let x = x - (POS_1BYTE_MAX + 1);
+        buf.push(POS_2BYTE_MARKER | (x >> 8) as u8 & 0x1f);
+        buf.push(x as u8);
+    } else {
+        // The length is counted in bytes, so POS_2BYTE_MAX + 1 is just the
+        // marker, as in WiredTiger's `__wt_vpack_posint`.
+        let x = x - (POS_2BYTE_MAX + 1);
+        let len = 8 - x.leading_zeros() as usize / 8;
+        buf.push(POS_MULTI_MARKER | len as u8);
+        buf.extend_from_slice(&x.to_be_bytes()[8 - len..]);
+    }
+}
+
+fn pack_int(buf: &mut Vec<u8>, x: i64) {
+    if x < NEG_2BYTE_MIN {
+        // Negative numbers record how many leading 0xff bytes were dropped.
+        let x = x as u64;
+        let lz = (!x).leading_zeros() as usize / 8;
+        buf.push(NEG_MULTI_MARKER | (lz & 0xf) as u8);
+        buf.extend_from_slice(&x.to_be_bytes()[lz..]);
+    } else if x < NEG_1BYTE_MIN {
+        let x = (x - NEG_2BYTE_MIN) as u64;
+        buf.push(NEG_2BYTE_MARKER | (x >> 8) as u8 & 0x1f);
+        buf.push(x as u8);
+    } else if x < 0 {
+        buf.push(NEG_1BYTE_MARKER | (x - NEG_1BYTE_MIN) as u8 & 0x3f);
+    } else {
+        pack_uint(buf, x as u64);
+    }
+}
+
+// Reads from the front of a buffer being unpacked.
+struct Reader<'b> {
+    buf: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8]> {
+        if len > self.buf.len() {
+            return Err(Error::new("unexpected end of packed data"));
+        }
+        let (taken, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn be_bytes(&mut self, len: usize, init: u64) -> Result<u64> {
+        if len > 8 {
+            return Err(Error::new("invalid packed integer"));
+        }
+        Ok(self
+            .take(len)?
+            .iter()
+            .fold(init, |x, b| x << 8 | u64::from(*b)))
+    }
+
+    fn uint(&mut self) -> Result<u64> {
+        let first = self.byte()?;
+        match first & 0xf0 {
+            0x80..=0xb0 => Ok(u64::from(first & 0x3f)),
+            0xc0 | 0xd0 => {
+                let x = u64::from(first & 0x1f) << 8 | u64::from(self.byte()?);
+                Ok(x + POS_1BYTE_MAX + 1)
+            }
+            0xe0 => {
+                let x = self.be_bytes(usize::from(first & 0xf), 0)?;
+                x.checked_add(POS_2BYTE_MAX + 1)
+                    .ok_or_else(|| Error::new("invalid packed integer"))
+            }
+            _ => Err(Error::new("invalid packed unsigned integer")),
+        }
+    }
+
+    fn int(&mut self) -> Result<i64> {
+        let first = self.buf.first().copied().unwrap_or_default();
+        match first & 0xf0 {
+            0x10 => {
+                self.byte()?;
+                let len = 8usize.saturating_sub(usize::from(first & 0xf));
+                Ok(self.be_bytes(len, u64::MAX)? as i64)
+            }
+            0x20 | 0x30 => {
+                self.byte()?;
+                let x = i64::from(first & 0x1f) << 8 | i64::from(self.byte()?);
+                Ok(x + NEG_2BYTE_MIN)
+            }
+            0x40..=0x70 => {
+                self.byte()?;
+                Ok(i64::from(first & 0x3f) + NEG_1BYTE_MIN)
+            }
+            _ => i64::try_from(self.uint()?).map_err(|_| Error::new("packed integer out of range")),
+        }
+    }
+}
+
+fn mismatch(format: &str, c: char, value: &WtValue) -> Error {
+    Error::with_kind(
+        ErrorKind::FormatMismatch,
+        format!(
+            "format mismatch: can't pack {:?} as {:?} in format {:?}",
+            value, c, format
+        ),
+    )
+}
+
+/// Packs `values` as described by `format`, the way WiredTiger stores keys and
+/// values, e.g. for the raw items of a cursor opened with `raw`.
+pub fn pack(format: &str, values: &[WtValue]) -> Result<Vec<u8>> {
+    let items = items(format)?;
+    let field_count = items.iter().filter(|(c, _)| *c != 'x').count();
+    if values.len() != field_count {
+        return Err(Error::with_kind(
+            ErrorKind::FormatMismatch,
+            format!(
+                "format {:?} has {} field(s) but {} value(s) were supplied",
+                format,
+                field_count,
+                values.len()
+            ),
+        ));
+    }
+
+    let mut buf = Vec::new();
+    let mut values = values.iter();
+    let last = items.len().saturating_sub(1);
+    for (i, (c, size)) in items.iter().copied().enumerate() {
+        if c == 'x' {
+            buf.resize(buf.len() + size.unwrap_or(1), 0);
+            continue;
+        }
+        let value = values.next().unwrap();
+        match (c, value) {
+            ('b', WtValue::Int(x)) => {
+                let x = i8::try_from(*x).map_err(|_| mismatch(format, c, value))?;
+                buf.push((x as u8) ^ 0x80);
+            }
+            ('h' | 'i' | 'l' | 'q', WtValue::Int(x)) => pack_int(&mut buf, *x),
+            ('B', WtValue::UInt(x)) => {
+                buf.push(u8::try_from(*x).map_err(|_| mismatch(format, c, value))?)
+            }
+            ('t', WtValue::UInt(x)) => {
+                let bits = size.unwrap_or(1).min(8);
+                if *x >= 1 << bits {
+                    return Err(mismatch(format, c, value));
+                }
+                buf.push(*x as u8);
+            }
+            ('H' | 'I' | 'L' | 'Q' | 'r', WtValue::UInt(x)) => pack_uint(&mut buf, *x),
+            ('s' | 'S', WtValue::Str(s)) => match size {
+                // Fixed-length strings are truncated or padded with nuls.
+                Some(size) => {
+                    let bytes = &s.as_bytes()[..s.len().min(size)];
+                    buf.extend_from_slice(bytes);
+                    buf.resize(buf.len() + size - bytes.len(), 0);
+                }
+                None if c == 's' => buf.push(s.bytes().next().unwrap_or(0)),
+                None => {
+                    if s.contains('\0') {
+                        return Err(mismatch(format, c, value));
+                    }
+                    buf.extend_from_slice(s.as_bytes());
+                    buf.push(0);
+                }
+            },
+            ('u', WtValue::Bytes(bytes)) => {
+                match size {
+                    Some(size) if bytes.len() != size => return Err(mismatch(format, c, value)),
+                    // Only the last field can leave its length implicit.
+                    None if i != last => pack_uint(&mut buf, bytes.len() as u64),
+                    _ => {}
+                }
+                buf.extend_from_slice(bytes);
+            }
+            _ => return Err(mismatch(format, c, value)),
+        }
+    }
+    Ok(buf)
+}
+
+/// Unpacks `buf`, packed as described by `format`, into one `WtValue` per field.
+pub fn unpack(format: &str, buf: &[u8]) -> Result<Vec<WtValue>> {
+    let items = items(format)?;
+    let mut reader = Reader { buf };
+    let mut values = Vec::with_capacity(items.len());
+    let last = items.len().saturating_sub(1);
+    for (i, (c, size)) in items.iter().copied().enumerate() {
+        let value = match c {
+            'x' => {
+                reader.take(size.unwrap_or(1))?;
+                continue;
+            }
+            'b' => WtValue::Int(i64::from((reader.byte()? ^ 0x80) as i8)),
+            'h' | 'i' | 'l' | 'q' => WtValue::Int(reader.int()?),
+            'B' | 't' => WtValue::UInt(u64::from(reader.byte()?)),
+            'H' | 'I' | 'L' | 'Q' | 'r' => WtValue::UInt(reader.uint()?),
+            's' | 'S' => {
+                let bytes = match size {
+                    Some(size) => reader.take(size)?,
+                    None if c == 's' => reader.take(1)?,
+                    None => {
+                        let len = reader
+                            .buf
+                            .iter()
+                            .position(|b| *b == 0)
+                            .ok_or_else(|| Error::new("unterminated packed string"))?;
+                        let bytes = reader.take(len)?;
+                        reader.take(1)?;
+                        bytes
+                    }
+                };
+                let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                WtValue::Str(String::from_utf8_lossy(&bytes[..end]).into_owned())
+            }
+            _ => {
+                let len = match size {
+                    Some(size) => size,
+                    None if i == last => reader.buf.len(),
+                    None => usize::try_from(reader.uint()?)
+                        .map_err(|_| Error::new("invalid packed length"))?,
+                };
+                WtValue::Bytes(reader.take(len)?.to_vec())
+            }
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fields, from_fields, pack, unpack, WtValue};
+
+    fn round_trip(format: &str, values: &[WtValue]) -> Vec<u8> {
+        let packed = pack(format, values).unwrap();
+        assert_eq!(unpack(format, &packed).unwrap(), values);
+        packed
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(fields("Si").unwrap(), ['S', 'i']);
+        assert_eq!(fields("u").unwrap(), ['u']);
+        assert_eq!(fields(">10s2q").unwrap(), ['s', 'q', 'q']);
+        assert_eq!(fields("3xi").unwrap(), ['i']);
+        assert!(fields("Sz").is_err());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        use WtValue::*;
+        assert_eq!(
+            round_trip("iiS", &[Int(1), Int(-1), Str("abc".to_string())]),
+            b"\x81\x7fabc\0"
+        );
+        round_trip("qS", &[Int(i64::MIN), Str(String::new())]);
+        round_trip("qS", &[Int(i64::MAX), Str("x".to_string())]);
+        assert_eq!(round_trip("u", &[Bytes(b"raw\0".to_vec())]), b"raw\0");
+        // Only a trailing `u` leaves out its length.
+        assert_eq!(
+            round_trip("uQ", &[Bytes(b"ab".to_vec()), UInt(30)]),
+            b"\x82ab\x9e"
+        );
+        round_trip(
+            "3s2xbBt",
+            &[Str("ab".to_string()), Int(-3), UInt(200), UInt(1)],
+        );
+    }
+
+    #[test]
+    fn test_integers() {
+        let boundaries = [
+            0,
+            63,
+            64,
+            8255,
+            8256,
+            8257,
+            1 << 40,
+            -1,
+            -64,
+            -65,
+            -8256,
+            -8257,
+            -(1 << 40),
+            i64::MIN,
+            i64::MAX,
+        ];
+        let mut packed: Vec<(i64, Vec<u8>)> = boundaries
+            .iter()
+            .map(|x| (*x, round_trip("q", &[WtValue::Int(*x)])))
+            .collect();
+        // The encodings sort in the same order as the numbers.
+        packed.sort_by(|a, b| a.1.cmp(&b.1));
+        assert!(packed.windows(2).all(|w| w[0].0 < w[1].0));
+
+        for x in [0, 63, 64, 8255, 8256, 8257, u64::MAX] {
+            round_trip("Q", &[WtValue::UInt(x)]);
+        }
+    }
+
+    #[test]
+    fn test_integer_encodings() {
+        // The bytes WiredTiger itself stores, from intpack.i.
+        for (x, bytes) in [
+            (0, &[0x80][..]),
+            (63, &[0xbf]),
+            (64, &[0xc0, 0x00]),
+            (8255, &[0xdf, 0xff]),
+            (8256, &[0xe0]),
+            (8257, &[0xe1, 0x01]),
+            (8256 + 256, &[0xe2, 0x01, 0x00]),
+        ] {
+            assert_eq!(pack("Q", &[WtValue::UInt(x)]).unwrap(), bytes, "{}", x);
+        }
+        for (x, bytes) in [
+            (-1, &[0x7f][..]),
+            (-64, &[0x40]),
+            (-65, &[0x3f, 0xff]),
+            (-8256, &[0x20, 0x00]),
+            (-8257, &[0x16, 0xdf, 0xbf]),
+        ] {
+            assert_eq!(pack("q", &[WtValue::Int(x)]).unwrap(), bytes, "{}", x);
+        }
+    }
+
+    #[test]
+    fn test_pack_empty_format() {
+        assert_eq!(pack("", &[]).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_pack_mismatch() {
+        assert!(pack("S", &[WtValue::Int(1)]).is_err());
+        assert!(pack("SS", &[WtValue::Str("one".to_string())]).is_err());
+        assert!(pack("b", &[WtValue::Int(200)]).is_err());
+        assert!(unpack("S", b"unterminated").is_err());
+    }
+
+    #[test]
+    fn test_from_fields() {
+        use WtValue::*;
+        let fields = || vec![Int(-1), Str("a".to_string()), UInt(2)];
+        assert_eq!(
+            from_fields::<(i64, String, u64)>(fields()).unwrap(),
+            (-1, "a".to_string(), 2)
+        );
+        assert!(from_fields::<(i64, String)>(fields()).is_err());
+        assert!(from_fields::<(i64, u64, u64)>(fields()).is_err());
+        assert_eq!(
+            from_fields::<Vec<u8>>(vec![Bytes(b"x".to_vec())]).unwrap(),
+            b"x"
+        );
+    }
+}
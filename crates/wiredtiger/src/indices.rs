@@ -0,0 +1,183 @@
+use crate::raw_api::{Error, Result};
+use crate::Session;
+
+/// The outcome of `Session::check_indices`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndexCheckReport {
+    /// The number of rows of the primary table that were checked.
+    pub rows_checked: u64,
+    /// `(index, primary key)` for each row with no matching entry in that index.
+    /// The key is packed, as WiredTiger stores it.
+    pub missing: Vec<(String, Vec<u8>)>,
+    /// `(index, count)` for each index holding entries that match no row.
+    pub extra: Vec<(String, u64)>,
+}
+
+impl IndexCheckReport {
+    /// Whether every index matched the table exactly.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+// Returns the value of `key` in a metadata config string, without quotes or the
+// parentheses around a list.
+pub(crate) fn config_value<'c>(config: &'c str, key: &str) -> Option<&'c str> {
+    let rest = config.match_indices(key).find_map(|(i, _)| {
+        if i > 0 && config.as_bytes()[i - 1] != b',' {
+            return None;
+        }
+        config[i + key.len()..].strip_prefix('=')
+    })?;
+    let value = match rest.as_bytes().first() {
+        Some(b'(') => &rest[1..rest.find(')')?],
+        Some(b'"') => &rest[1..rest[1..].find('"')? + 1],
+        _ => &rest[..rest.find(',').unwrap_or(rest.len())],
+    };
+    Some(value)
+}
+
+impl Session {
+    /// Checks that every index of `table` (e.g. `table:people`) has exactly one
+    /// entry for each row, by recomputing each row's index key and looking it up
+    /// in the index. Indices using a custom extractor can't be checked.
+    pub fn check_indices(&self, table: &str) -> Result<IndexCheckReport> {
+        let name = table
+            .strip_prefix("table:")
+            .ok_or_else(|| Error::new(format!("{} isn't a table", table)))?;
+
+        // Each index's metadata lists its columns (the indexed columns followed
+        // by the primary key's) and the file it's stored in.
+        let mut indices = Vec::new();
+        let metadata = self.open_cursor("metadata:")?;
+        let prefix = format!("index:{}:", name);
+        metadata.set_key(&prefix)?;
+        metadata.search_near()?;
+        loop {
+            let (uri, config) = metadata.get_raw_key_value()?;
+            let uri = String::from_utf8_lossy(&uri.unwrap_or_default())
+                .trim_end_matches('\0')
+                .to_string();
+            if uri > prefix && !uri.starts_with(&prefix) {
+                break;
+            }
+            if uri.starts_with(&prefix) {
+                let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+                if config_value(&config, "extractor").is_some_and(|e| !e.is_empty()) {
+                    return Err(Error::new(format!(
+                        "can't check {}: it uses a custom extractor",
+                        uri
+                    )));
+                }
+                let columns = config_value(&config, "columns")
+                    .ok_or_else(|| Error::new(format!("no columns in metadata for {}", uri)))?;
+                let source = config_value(&config, "source")
+                    .ok_or_else(|| Error::new(format!("no source file in metadata for {}", uri)))?;
+                indices.push((uri, columns.to_string(), source.to_string()));
+            }
+            match metadata.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut report = IndexCheckReport::default();
+        for (index, columns, source) in indices {
+            // Projecting the table onto the index's columns packs them exactly
+            // as the index file's key.
+            let rows = self.open_cursor_with_config(&format!("{}({})", table, columns), "raw")?;
+            let entries = self.open_cursor_with_config(&source, "raw")?;
+            let mut row_count = 0;
+            let mut found = 0;
+            loop {
+                match rows.next() {
+                    Ok(()) => {}
+                    Err(err) if err.is_not_found() => break,
+                    Err(err) => return Err(err),
+                }
+                row_count += 1;
+                let (key, index_key) = rows.raw_cursor.get_packed_key_value()?;
+                entries.raw_cursor.set_key_item(&index_key)?;
+                match entries.search() {
+                    Ok(()) => found += 1,
+                    Err(err) if err.is_not_found() => report.missing.push((index.clone(), key)),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            entries.reset()?;
+            let mut entry_count = 0;
+            loop {
+                match entries.next() {
+                    Ok(()) => entry_count += 1,
+                    Err(err) if err.is_not_found() => break,
+                    Err(err) => return Err(err),
+                }
+            }
+            if entry_count > found {
+                report.extra.push((index, entry_count - found));
+            }
+            report.rows_checked = row_count;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::config_value;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_config_value() {
+        let config = "collator=,columns=(dept,id),extractor=,source=\"file:t_dept.wti\"";
+        assert_eq!(config_value(config, "columns"), Some("dept,id"));
+        assert_eq!(config_value(config, "source"), Some("file:t_dept.wti"));
+        assert_eq!(config_value(config, "extractor"), Some(""));
+        assert_eq!(config_value(config, "id"), None);
+    }
+
+    #[test]
+    fn test_check_indices() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create(
+            "table:people",
+            "key_format=S,value_format=S,columns=(id,dept)"
+        ));
+        assert_ok!(sess.create("index:people:dept", "columns=(dept)"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:people"));
+            for (id, dept) in [("alice", "eng"), ("bob", "ops"), ("carol", "eng")] {
+                assert_ok!(cur.set_key(id));
+                assert_ok!(cur.set_value(dept));
+                assert_ok!(cur.insert());
+            }
+        }
+
+        let report = assert_ok!(sess.check_indices("table:people"));
+        assert!(report.is_clean());
+        assert_eq!(report.rows_checked, 3);
+
+        // Remove an entry from the index's file behind the table's back.
+        {
+            let cur = assert_ok!(sess.open_cursor("file:people_dept.wti"));
+            assert_ok!(cur.next());
+            assert_ok!(cur.remove());
+        }
+
+        let report = assert_ok!(sess.check_indices("table:people"));
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.missing,
+            [("index:people:dept".to_string(), b"alice\0".to_vec())]
+        );
+        assert!(report.extra.is_empty());
+    }
+}
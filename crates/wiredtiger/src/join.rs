@@ -0,0 +1,194 @@
+use crate::raw_api::Result;
+use crate::{Cursor, JoinConfig, Session};
+use std::ops::Deref;
+
+/// Builds a join cursor, which iterates over the records of a table that match
+/// every cursor added to it. Start one with `Session::join`.
+pub struct JoinBuilder<'a> {
+    join: Cursor<'a>,
+    refs: Vec<Cursor<'a>>,
+}
+
+/// A join cursor, built by `JoinBuilder::build`. It derefs to a `Cursor` over
+/// the matching records of the table.
+pub struct JoinCursor<'a> {
+    // Declared first so it's closed before the cursors it refers to.
+    join: Cursor<'a>,
+    _refs: Vec<Cursor<'a>>,
+}
+
+impl Session {
+    /// Opens an empty join cursor over `table` (e.g. `table:people`), to be
+    /// passed to `JoinBuilder::new`.
+    pub fn open_join_cursor(&self, table: &str) -> Result<Cursor<'_>> {
+        self.open_cursor(&format!("join:{}", table))
+    }
+
+    /// Starts a join over `table` (e.g. `table:people`).
+    pub fn join(&self, table: &str) -> Result<JoinBuilder<'_>> {
+        Ok(JoinBuilder::new(self.open_join_cursor(table)?))
+    }
+}
+
+impl<'a> JoinBuilder<'a> {
+    /// Starts a join on `join`, a cursor from `Session::open_join_cursor`.
+    pub fn new(join: Cursor<'a>) -> Self {
+        JoinBuilder {
+            join,
+            refs: Vec::new(),
+        }
+    }
+
+    /// Restricts the join to records matching `cursor`, an index or table cursor
+    /// positioned with `search` or `search_near`. `config` chooses how records
+    /// are compared with the cursor's position and whether to use a Bloom filter.
+    pub fn add_with(mut self, cursor: Cursor<'a>, config: JoinConfig) -> Result<Self> {
+        self.join.session.raw_session.join(
+            &self.join.raw_cursor,
+            &cursor.raw_cursor,
+            &config.to_string(),
+        )?;
+        self.refs.push(cursor);
+        Ok(self)
+    }
+
+    pub fn build(self) -> JoinCursor<'a> {
+        JoinCursor {
+            join: self.join,
+            _refs: self.refs,
+        }
+    }
+}
+
+impl<'a> Deref for JoinCursor<'a> {
+    type Target = Cursor<'a>;
+
+    fn deref(&self) -> &Cursor<'a> {
+        &self.join
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Compare, Connection, JoinBuilder, JoinConfig, JoinOperation, Session};
+    use assert_ok::assert_ok;
+
+    // The ids of people at least 30 years old who live in `city`.
+    fn join_ids(sess: &Session, city: &str, bloom: bool) -> Vec<String> {
+        let age = assert_ok!(sess.open_cursor("index:people:age"));
+        assert_ok!(age.set_key("30"));
+        assert_ok!(age.search());
+        let home = assert_ok!(sess.open_cursor("index:people:city"));
+        assert_ok!(home.set_key(city));
+        assert_ok!(home.search());
+
+        let joined = assert_ok!(assert_ok!(assert_ok!(sess.join("table:people")).add_with(
+            age,
+            JoinConfig {
+                compare: Compare::Ge,
+                ..JoinConfig::default()
+            }
+        ))
+        .add_with(
+            home,
+            JoinConfig {
+                compare: Compare::Eq,
+                count: 10,
+                bloom,
+                ..JoinConfig::default()
+            }
+        ))
+        .build();
+
+        collect_ids(&joined)
+    }
+
+    fn collect_ids(joined: &crate::Cursor) -> Vec<String> {
+        let mut ids = Vec::new();
+        loop {
+            match joined.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => panic!("{:?}", err),
+            }
+            let (id, _) = assert_ok!(joined.get_raw_key_value());
+            ids.push(String::from_utf8(id.unwrap()).unwrap());
+        }
+        ids
+    }
+
+    fn create_people(sess: &Session) {
+        assert_ok!(sess.create(
+            "table:people",
+            "key_format=S,value_format=SS,columns=(id,age,city)"
+        ));
+        assert_ok!(sess.create("index:people:age", "columns=(age)"));
+        assert_ok!(sess.create("index:people:city", "columns=(city)"));
+        // Values are packed "SS", so write them raw.
+        let cur = assert_ok!(sess.open_cursor_with_config("table:people", "raw"));
+        for (id, age, city) in [
+            ("a", "20", "nyc"),
+            ("b", "30", "nyc"),
+            ("c", "35", "sf"),
+            ("d", "30", "nyc"),
+            ("e", "40", "nyc"),
+        ] {
+            assert_ok!(cur.raw_cursor.set_key_item(format!("{}\0", id).as_bytes()));
+            assert_ok!(cur
+                .raw_cursor
+                .set_value_item(format!("{}\0{}\0", age, city).as_bytes()));
+            assert_ok!(cur.insert());
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        create_people(&sess);
+
+        let plain = join_ids(&sess, "nyc", false);
+        assert_eq!(plain, ["b", "d", "e"]);
+        assert_eq!(join_ids(&sess, "nyc", true), plain);
+    }
+
+    #[test]
+    fn test_join_or() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        create_people(&sess);
+
+        // People younger than 30 or living in sf.
+        let age = assert_ok!(sess.open_cursor("index:people:age"));
+        assert_ok!(age.set_key("30"));
+        assert_ok!(age.search());
+        let home = assert_ok!(sess.open_cursor("index:people:city"));
+        assert_ok!(home.set_key("sf"));
+        assert_ok!(home.search());
+        let or = JoinConfig {
+            operation: JoinOperation::Or,
+            ..JoinConfig::default()
+        };
+        let joined = assert_ok!(assert_ok!(JoinBuilder::new(assert_ok!(
+            sess.open_join_cursor("table:people")
+        ))
+        .add_with(
+            age,
+            JoinConfig {
+                compare: Compare::Lt,
+                ..or
+            }
+        ))
+        .add_with(home, or))
+        .build();
+        assert_eq!(collect_ids(&joined), ["a", "c"]);
+    }
+}
@@ -2,59 +2,458 @@ mod raw_api;
 
 mod config;
 
+pub mod format;
+
+mod dump;
+
+mod pool;
+
+mod collator;
+
+mod compressor;
+
+mod stats;
+
+mod compact;
+
+mod checkpoint;
+
+mod event;
+
+mod timestamp;
+
+mod indices;
+
+mod transaction;
+
+mod join;
+
+mod cas;
+
+mod os_cache;
+
+mod snapshot;
+
+mod cache;
+
+mod seek;
+
+mod batch;
+
+mod app_metadata;
+
+mod modify;
+
+mod scan;
+
+mod open_retry;
+
+mod quantiles;
+
+mod extractor;
+
+mod downgrade;
+
+#[cfg(feature = "tokio")]
+mod async_table;
+
+#[cfg(feature = "tokio")]
+pub use async_table::AsyncTable;
+pub use checkpoint::{Change, CheckpointInfo};
+pub use collator::CollatorFn;
+pub use compact::CompactReport;
+pub use compressor::Compressor;
+pub use config::{
+    CheckpointConfig, Compare, CreateConfig, CreateConfigBuilder, DirectIOSetting, DropConfig,
+    DropConfigBuilder, FileExtensionConfigOption, IsolationLevel, JoinConfig, JoinOperation,
+    OpenConnectionConfig, OpenConnectionConfigBuilder, OpenSessionConfig, Preset, StatisticsOption,
+    SyncMethodOption, TieredStorageConfig, VerboseOption,
+};
 use delegate::delegate;
-pub use raw_api::Error;
-use raw_api::{CompareStatus, RawConnection, Result};
+pub use event::{EventHandler, LogEventHandler};
+pub use extractor::{Extractor, IndexKey};
+pub use format::{FromWt, WtValue};
+pub use indices::IndexCheckReport;
+pub use join::{JoinBuilder, JoinCursor};
+pub use pool::{CursorCache, CursorPool, PooledCursor};
+pub use raw_api::{CompareStatus, Error, ErrorKind, LastError};
+use raw_api::{RawConnection, Result};
+pub use scan::KeyScan;
+pub use seek::Seek;
+pub use snapshot::{Snapshot, SnapshotSession};
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+pub use timestamp::Timestamp;
+pub use transaction::{Backoff, ConflictStats, RetryPolicy, Transaction};
+
+// Enters a `tracing` span named `$name` until the end of the enclosing block,
+// when the `tracing` feature is on; otherwise it expands to nothing.
+macro_rules! span {
+    ($name:literal, $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name, $($fields)*).entered();
+    };
+}
 
-struct Connection {
+struct ConnectionInner {
     raw_conn: raw_api::RawConnection,
+    config: String,
+    // Referenced by WiredTiger until the connection is closed, so it's dropped
+    // after the close in `Drop`.
+    event_handler: Option<Box<event::RustEventHandler<'static>>>,
+    checkpoint_on_close: AtomicBool,
+}
+
+/// An open database. Clones share the one connection, which is closed once the
+/// last clone and the last session opened from any of them have been dropped,
+/// so each thread can be handed a clone to open its own sessions with.
+#[derive(Clone)]
+pub struct Connection {
+    inner: Arc<ConnectionInner>,
 }
 
 impl Connection {
     pub fn open(filename: &str, options: &str) -> Result<Self> {
         let raw_conn = RawConnection::open(filename, options)?;
-        Ok(Self { raw_conn })
+        Ok(Self::from_raw(raw_conn, options, None))
+    }
+
+    fn from_raw(
+        raw_conn: RawConnection,
+        config: &str,
+        event_handler: Option<Box<event::RustEventHandler<'static>>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(ConnectionInner {
+                raw_conn,
+                config: config.to_string(),
+                event_handler,
+                checkpoint_on_close: AtomicBool::new(false),
+            }),
+        }
+    }
+    /// Opens (creating it if need be) the database `name` in a subdirectory of
+    /// `parent`, which is created if it's missing. `options` is added to `create`.
+    pub fn create_in(parent: &Path, name: &str, options: &str) -> Result<Self> {
+        let home = parent.join(name);
+        std::fs::create_dir_all(&home)
+            .map_err(|err| Error::new(format!("can't create {}: {}", home.display(), err)))?;
+        let home = home
+            .to_str()
+            .ok_or_else(|| Error::new(format!("{} isn't valid UTF-8", home.display())))?;
+        let options = if options.is_empty() {
+            "create".to_string()
+        } else {
+            format!("create,{}", options)
+        };
+        Self::open(home, &options)
+    }
+
+    /// Opens a new, empty database that's kept entirely in memory and lost when
+    /// the connection closes, e.g. for tests. `config` is added to
+    /// `in_memory=true,create`.
+    pub fn open_in_memory(config: &str) -> Result<Self> {
+        let mut options = "in_memory=true,create".to_string();
+        if !config.is_empty() {
+            options.push(',');
+            options.push_str(config);
+        }
+        // Nothing is written to the home directory, so it doesn't matter which
+        // it is.
+        Self::open("", &options)
+    }
+
+    /// Opens a connection configured by `config` rather than a config string.
+    pub fn open_with(filename: &str, config: &OpenConnectionConfig) -> Result<Self> {
+        Self::open(filename, &config.to_string())
+    }
+
+    /// Closes the connection with `config` (e.g. `leak_memory=true` for a faster
+    /// shutdown), rather than with the defaults as when it's dropped. Fails
+    /// without closing if any clone of the connection, or session opened from
+    /// it, is still around.
+    pub fn close(self, config: &str) -> Result<()> {
+        let inner = Arc::try_unwrap(self.inner).map_err(|_| {
+            Error::new("can't close a connection that's still shared with clones or sessions")
+        })?;
+        if inner.checkpoint_on_close.swap(false, Ordering::AcqRel) {
+            inner.final_checkpoint()?;
+        }
+        inner.raw_conn.close_with_config(config)
+    }
+
+    /// Makes closing the connection, by `close` or by dropping the last clone,
+    /// checkpoint first, for applications that don't checkpoint themselves.
+    /// When dropping, a failed checkpoint is logged and the close goes ahead.
+    pub fn with_checkpoint_on_close(self) -> Connection {
+        self.inner
+            .checkpoint_on_close
+            .store(true, Ordering::Release);
+        self
+    }
+
+    /// The config string the connection was opened with. WiredTiger can't be
+    /// asked for the settings in effect, so this is the only record of them.
+    pub fn config(&self) -> &str {
+        &self.inner.config
     }
+
+    /// Opens a session, which keeps the connection open for as long as it lives.
     pub fn open_session(&self) -> Result<Session> {
-        let raw_session = self.raw_conn.open_session()?;
+        self.open_session_with("")
+    }
+
+    /// Opens a session configured by `config`, e.g. `isolation=snapshot` or
+    /// `cache_cursors=false`, or the string form of an `OpenSessionConfig`.
+    pub fn open_session_with(&self, config: &str) -> Result<Session> {
+        let raw_session = self.raw_conn().open_session_with(config)?;
         Ok(Session {
             raw_session,
-            conn: &self,
+            conn: self.clone(),
+            conflict_stats: Cell::new(ConflictStats::default()),
+            cursors: RefCell::new(Vec::new()),
         })
     }
 
+    /// Lists every table in the database along with the configuration string it
+    /// was created with, as recorded in the `metadata:` cursor.
+    pub fn list_tables(&self) -> Result<Vec<(String, String)>> {
+        self.open_session()?.list_by_type("table:")
+    }
+
+    /// Drops every table (along with its indices and checkpoints), leaving an
+    /// empty database on the same connection. Meant for resetting between tests;
+    /// it fails if any of the tables is in use.
+    pub fn truncate_database(&self) -> Result<()> {
+        let session = self.open_session()?;
+        for (uri, _) in self.list_tables()? {
+            session.drop(&uri, "")?;
+        }
+        Ok(())
+    }
+
+    fn raw_conn(&self) -> &RawConnection {
+        &self.inner.raw_conn
+    }
+
     delegate! {
-        to self.raw_conn {
+        to self.inner.raw_conn {
+            /// The home directory WiredTiger opened. It's the path given to
+            /// `open`: `WIREDTIGER_HOME` only stands in when no path is given,
+            /// which this crate never does.
             pub fn get_home(&self) -> Result<String>;
+            /// Whether opening the connection created the database.
             pub fn is_new(&self) -> bool ;
+            pub fn load_extension(&self, path: &str, config: &str) -> Result<()>;
+            pub fn query_timestamp(&self, config: &str) -> Result<Timestamp>;
             pub fn reconfigure(&self, config: &str) -> Result<()>;
+            pub fn rollback_to_stable(&self, config: &str) -> Result<()>;
+            pub fn set_timestamp(&self, config: &str) -> Result<()>;
         }
     }
 }
 
+// The `open_cursor` config for reading at `checkpoint`.
+fn checkpoint_config(checkpoint: &str) -> Result<String> {
+    if checkpoint.is_empty() || checkpoint.contains([',', '=', '(', ')']) {
+        return Err(Error::new(format!(
+            "invalid checkpoint name: {:?}",
+            checkpoint
+        )));
+    }
+    Ok(format!("checkpoint={}", checkpoint))
+}
+
 impl std::fmt::Debug for Connection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "connection")
     }
 }
 
-impl<'a> Session<'a> {
+impl Session {
+    /// Closes the session, reporting any error rather than panicking as dropping
+    /// it does. It's consumed, so none of its cursors can still be open.
+    pub fn close(self) -> Result<()> {
+        // `Drop` closes it again, which is a no-op.
+        self.raw_session.close()
+    }
+
     pub fn open_cursor(&self, uri: &str) -> Result<Cursor> {
         let raw_cursor = self.raw_session.open_cursor(uri)?;
-        Ok(Cursor {
-            session: &self,
+        Ok(self.track(raw_cursor))
+    }
+
+    pub fn open_cursor_with_config(&self, uri: &str, config: &str) -> Result<Cursor<'_>> {
+        let raw_cursor = self.raw_session.open_cursor_with_config(uri, config)?;
+        Ok(self.track(raw_cursor))
+    }
+
+    /// Opens a cursor with `overwrite=false`, whose `insert` leaves existing keys
+    /// alone and whose `update` and `remove` fail on missing ones.
+    pub fn open_cursor_no_overwrite(&self, uri: &str) -> Result<Cursor<'_>> {
+        self.open_cursor_with_config(uri, "overwrite=false")
+    }
+
+    fn track(&self, raw_cursor: raw_api::RawCursor) -> Cursor<'_> {
+        self.cursors.borrow_mut().push(raw_cursor.handle());
+        Cursor {
+            session: self,
             raw_cursor,
-        })
+        }
+    }
+
+    /// The number of this session's cursors that are currently open.
+    pub fn open_cursor_count(&self) -> usize {
+        self.cursors.borrow().len()
+    }
+
+    /// Resets every open cursor of this session, leaving them unpositioned and
+    /// releasing the resources they pin. Unlike `reset`, it leaves the rest of
+    /// the session alone. Every cursor is reset even if one fails, and the first
+    /// failure is returned.
+    pub fn reset_all_cursors(&self) -> Result<()> {
+        let mut result = Ok(());
+        for cursor in self.cursors.borrow().iter() {
+            let reset = unsafe { cursor.reset() };
+            if result.is_ok() {
+                result = reset;
+            }
+        }
+        result
+    }
+
+    /// Lists every metadata entry whose URI starts with `prefix` (e.g. `index:`,
+    /// `colgroup:` or `file:`) along with its configuration string.
+    pub fn list_by_type(&self, prefix: &str) -> Result<Vec<(String, String)>> {
+        let cursor = self.open_cursor("metadata:")?;
+        let mut entries = Vec::new();
+        loop {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+            let (uri, config) = cursor.get_raw_key_value()?;
+            let uri = String::from_utf8_lossy(&uri.unwrap_or_default()).into_owned();
+            if uri.starts_with(prefix) {
+                let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+                entries.push((uri, config));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Removes every record of `uri`, which is left in place, empty. Without
+    /// cursors bounding a range WiredTiger can do this without visiting each
+    /// record.
+    pub fn truncate_table(&self, uri: &str) -> Result<()> {
+        self.raw_session.truncate(Some(uri), None, None, "")
+    }
+
+    /// Opens a cursor on `index` of `table` (with or without the `table:`
+    /// prefix). Its keys are the index columns, and its values are the table's
+    /// value columns.
+    pub fn open_index_cursor(&self, table: &str, index: &str) -> Result<Cursor<'_>> {
+        let table = table.strip_prefix("table:").unwrap_or(table);
+        self.open_cursor(&format!("index:{}:{}", table, index))
+    }
+
+    /// Opens a read-only cursor on `uri` as of the named `checkpoint`, for
+    /// point-in-time reads. WiredTiger refuses to drop a checkpoint while a cursor
+    /// is open on it, so the data stays readable for the life of the cursor.
+    pub fn open_checkpoint_cursor(&self, uri: &str, checkpoint: &str) -> Result<Cursor<'_>> {
+        self.open_cursor_with_config(uri, &checkpoint_config(checkpoint)?)
+    }
+
+    /// Controls whether this session can be drafted into eviction work when the
+    /// cache is full. Disabling participation (`ignore_cache_size=true`) is meant
+    /// for dedicated maintenance or background sessions whose latency matters more
+    /// than keeping the cache under its limit; application threads should normally
+    /// leave it enabled so they share the eviction load.
+    pub fn set_eviction_participation(&self, enabled: bool) -> Result<()> {
+        self.raw_session
+            .reconfigure(&format!("ignore_cache_size={}", !enabled))
+    }
+
+    /// Details of the last error returned by an operation on this session, for
+    /// telling apart failures that share an error code.
+    pub fn last_error(&self) -> LastError {
+        self.raw_session.get_last_error()
+    }
+
+    /// Creates `name` as configured by `config` rather than a config string. If
+    /// `config` names columns, there must be one for each key and value field.
+    pub fn create_with(&self, name: &str, config: &CreateConfig) -> Result<()> {
+        config.validate()?;
+        self.create(name, &config.to_string())
+    }
+
+    /// Drops `name` as configured by `config` rather than a config string.
+    pub fn drop_with(&self, name: &str, config: &DropConfig) -> Result<()> {
+        self.drop(name, &config.to_string())
+    }
+
+    /// Changes the isolation level of this session's transactions from now on.
+    /// It can't be called inside a transaction.
+    pub fn set_isolation(&self, level: IsolationLevel) -> Result<()> {
+        self.reconfigure(&format!("isolation={}", level.token()))
+    }
+
+    pub fn begin_transaction(&self, config: &str) -> Result<()> {
+        span!("begin_transaction", config = %config);
+        self.raw_session.begin_transaction(config)
+    }
+
+    pub fn commit_transaction(&self, config: &str) -> Result<()> {
+        span!("commit_transaction", config = %config);
+        self.raw_session.commit_transaction(config)
+    }
+
+    /// Begins a transaction at the given isolation level. `config` holds any
+    /// other `begin_transaction` settings, and may be empty.
+    pub fn begin_transaction_with(&self, isolation: IsolationLevel, config: &str) -> Result<()> {
+        let mut full_config = format!("isolation={}", isolation.token());
+        if !config.is_empty() {
+            full_config.push(',');
+            full_config.push_str(config);
+        }
+        self.begin_transaction(&full_config)
+    }
+
+    /// Drops `uri` if it contains no records. Returns whether it was dropped.
+    pub fn drop_if_empty(&self, uri: &str) -> Result<bool> {
+        {
+            // The cursor has to be closed before the drop, or it fails with EBUSY.
+            let cursor = self.open_cursor(uri)?;
+            match cursor.next() {
+                Ok(()) => return Ok(false),
+                Err(err) if err.is_not_found() => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.drop(uri, "")?;
+        Ok(true)
     }
 
     delegate! {
         to self.raw_session{
+            pub fn alter(&self, name: &str, config: &str) -> Result<()>;
+            pub fn checkpoint(&self, config: &str) -> Result<()>;
             pub fn create(&self, name: &str, config: &str) -> Result<()>;
             pub fn compact(&self, name: &str, config: &str) -> Result<()>;
             pub fn drop(&self, name: &str, config: &str) -> Result<()>;
+            pub fn flush_tier(&self, config: &str) -> Result<()>;
+            pub fn log_flush(&self, config: &str) -> Result<()>;
+            pub fn log_printf(&self, msg: &str) -> Result<()>;
+            pub fn query_timestamp(&self, config: &str) -> Result<Timestamp>;
             pub fn reconfigure(&self,  config: &str) -> Result<()>;
             pub fn reset(&self) -> Result<()>;
             pub fn reset_snapshot(&self) -> Result<()>;
+            pub fn rollback_transaction(&self, config: &str) -> Result<()>;
+            pub fn timestamp_transaction(&self, config: &str) -> Result<()>;
+            pub fn transaction_pinned_range(&self) -> Result<u64>;
         }
     }
 }
@@ -68,35 +467,197 @@ impl<'a> Cursor<'a> {
         self.raw_cursor.equals(&other.raw_cursor)
     }
 
+    /// Decodes the current key and value as `K` and `V`, driven by the cursor's
+    /// key and value formats. A single field decodes as `String`, `i64`, `u64`
+    /// or `Vec<u8>`, depending on its type, and several as a tuple of those.
+    pub fn get_key_value<K: FromWt, V: FromWt>(&self) -> Result<(K, V)> {
+        let (key, value) = self.raw_cursor.get_packed_key_value()?;
+        let key = format::unpack(self.key_format(), &key)?;
+        let value = format::unpack(self.value_format(), &value)?;
+        Ok((format::from_fields(key)?, format::from_fields(value)?))
+    }
+
+    /// The current key, packed as WiredTiger stores it, without copying it out of
+    /// WiredTiger's memory. The bytes borrow the cursor mutably, so moving or
+    /// changing it while they're held doesn't compile:
+    ///
+    /// ```compile_fail
+    /// # fn f(cursor: &mut wiredtiger::Cursor) -> Result<(), wiredtiger::Error> {
+    /// let key = unsafe { cursor.key_bytes()? };
+    /// cursor.next()?;
+    /// println!("{:?}", key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The bytes must not be used once the cursor has been positioned, reset or
+    /// closed through the session instead, which the borrow can't prevent:
+    /// `Session::reset_all_cursors`, `Session::reset`, committing or rolling
+    /// back a transaction, or closing the session.
+    pub unsafe fn key_bytes(&mut self) -> Result<&[u8]> {
+        let (key, _) = self.raw_cursor.packed_key_value_slices()?;
+        Ok(key)
+    }
+
+    /// Like `key_bytes`, but for the current value.
+    ///
+    /// ```compile_fail
+    /// # fn f(cursor: &mut wiredtiger::Cursor) -> Result<(), wiredtiger::Error> {
+    /// let value = unsafe { cursor.value_bytes()? };
+    /// cursor.next()?;
+    /// println!("{:?}", value);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// As for `key_bytes`.
+    pub unsafe fn value_bytes(&mut self) -> Result<&[u8]> {
+        let (_, value) = self.raw_cursor.packed_key_value_slices()?;
+        Ok(value)
+    }
+
+    /// The current value for display: as text if it's valid UTF-8, and otherwise
+    /// as hex prefixed with `0x`. An `S` value's nul terminator isn't included.
+    pub fn get_display_value(&self) -> Result<String> {
+        let (_, mut value) = self.raw_cursor.get_packed_key_value()?;
+        if self.value_format() == "S" && value.last() == Some(&0) {
+            value.pop();
+        }
+        Ok(match String::from_utf8(value) {
+            Ok(text) => text,
+            Err(err) => {
+                let hex: String = err
+                    .as_bytes()
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                format!("0x{}", hex)
+            }
+        })
+    }
+
+    /// Inserts `value` into a record number (`key_format=r`) table under the next
+    /// free record number, returning it. The cursor must have been opened with
+    /// `append`, and the value format must be `u`.
+    pub fn append(&self, value: &[u8]) -> Result<u64> {
+        self.raw_cursor.set_value_item(value)?;
+        self.insert()?;
+        let (key, _) = self.raw_cursor.get_packed_key_value()?;
+        match format::unpack("r", &key)?.as_slice() {
+            [WtValue::UInt(recno)] => Ok(*recno),
+            _ => Err(Error::new("appended record has no record number")),
+        }
+    }
+
+    /// Searches for `key` (packed, or the bytes of a `u` key) with
+    /// `search_near`, returning how the key found compares with it, along with
+    /// the packed key and value found, or `None` if the object is empty.
+    pub fn search_near_full(&self, key: &[u8]) -> Result<Option<NearRecord>> {
+        self.raw_cursor.set_key_item(key)?;
+        let status = match self.search_near() {
+            Ok(status) => status,
+            Err(err) if err.is_not_found() => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let (key, value) = self.raw_cursor.get_packed_key_value()?;
+        Ok(Some((status, key, value)))
+    }
+
+    /// Sets the key to `key` (packed, or the bytes of a `u` key) and reserves its
+    /// record for the current transaction, as if it had been updated. A
+    /// concurrent transaction that then writes it gets a conflict instead, so
+    /// this transaction's own update later on can't. The record must exist.
+    pub fn reserve_for_update(&self, key: &[u8]) -> Result<()> {
+        self.raw_cursor.set_key_item(key)?;
+        self.reserve()
+    }
+
+    /// Inserts the cursor's key and value, returning whether it was inserted. On
+    /// a cursor opened with `overwrite=false` (see
+    /// `Session::open_cursor_no_overwrite`) a key that's already present is left
+    /// alone and `Ok(false)` is returned; otherwise the value is overwritten.
+    pub fn insert(&self) -> Result<bool> {
+        span!("insert", uri = %self.raw_cursor.uri());
+        match self.raw_cursor.insert() {
+            Ok(()) => Ok(true),
+            Err(err) if err.is_duplicate_key() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn next(&self) -> Result<()> {
+        span!("next", uri = %self.raw_cursor.uri());
+        self.raw_cursor.next()
+    }
+
+    pub fn search(&self) -> Result<()> {
+        span!("search", uri = %self.raw_cursor.uri());
+        self.raw_cursor.search()
+    }
+
+    /// Positions the cursor on the largest key and returns it, packed, or `None`
+    /// if the object is empty. Only the key can be read at that position, so the
+    /// cursor should be reset or moved before being used for anything else.
+    pub fn largest_key_bytes(&self) -> Result<Option<Vec<u8>>> {
+        match self.largest_key() {
+            Ok(()) => Ok(Some(self.raw_cursor.get_packed_key_value()?.0)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     delegate! {
         to self.raw_cursor{
             pub fn bound(&self, config: &str) -> Result<()> ;
             pub fn get_raw_key_value(&self) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>)>;
-            pub fn insert(&self) -> Result<()>;
+            pub fn get_value(&self) -> Result<Vec<WtValue>>;
+            pub fn key_format(&self) -> &str;
             pub fn largest_key(&self) -> Result<()>;
             // int WT_CURSOR::modify	(	WT_CURSOR * 	cursor, WT_MODIFY * 	entries, int 	nentries )
-            pub fn next(&self) -> Result<()>;
             pub fn prev(&self) -> Result<()>;
             pub fn reconfigure(&self, config: &str) -> Result<()>;
             pub fn remove(&self) -> Result<()>;
             pub fn reserve(&self) -> Result<()>;
             pub fn reset(&self) -> Result<()> ;
-            pub fn search(&self) -> Result<()> ;
             pub fn search_near(&self) -> Result<CompareStatus> ;
             pub fn update(&self) -> Result<()>;
-            pub fn set_key(&self, key: &str);
-            pub fn set_value(&self, key: &str);
+            pub fn set_key(&self, key: &str) -> Result<()>;
+            pub fn set_value(&self, key: &str) -> Result<()>;
+            pub fn value_format(&self) -> &str;
         }
     }
 }
 
-impl Drop for Connection {
+impl ConnectionInner {
+    fn final_checkpoint(&self) -> Result<()> {
+        let session = self.raw_conn.open_session()?;
+        let result = session.checkpoint("");
+        session.close()?;
+        result
+    }
+}
+
+impl Drop for ConnectionInner {
     fn drop(&mut self) {
+        if self.checkpoint_on_close.load(Ordering::Acquire) {
+            if let Err(err) = self.final_checkpoint() {
+                log::warn!(
+                    "checkpoint before closing failed: {} ({})",
+                    err.message,
+                    err.code
+                );
+            }
+        }
+        // A no-op if `Connection::close` has closed it already.
         self.raw_conn.close().unwrap();
     }
 }
 
-impl<'a> Drop for Session<'a> {
+impl Drop for Session {
     fn drop(&mut self) {
         self.raw_session.close().unwrap();
     }
@@ -104,24 +665,39 @@ impl<'a> Drop for Session<'a> {
 
 impl<'a> Drop for Cursor<'a> {
     fn drop(&mut self) {
+        let handle = self.raw_cursor.handle();
+        self.session.cursors.borrow_mut().retain(|c| *c != handle);
         self.raw_cursor.close().unwrap();
     }
 }
 
-struct Cursor<'a> {
-    session: &'a Session<'a>,
+/// The record `Cursor::search_near_full` found: how its key compares with the
+/// one searched for, and its packed key and value.
+pub type NearRecord = (CompareStatus, Vec<u8>, Vec<u8>);
+
+pub struct Cursor<'a> {
+    session: &'a Session,
     raw_cursor: raw_api::RawCursor,
 }
 
-struct Session<'a> {
+pub struct Session {
     raw_session: raw_api::RawSession,
-    conn: &'a Connection,
+    // Keeps the connection open until after `Drop` has closed the session.
+    conn: Connection,
+    conflict_stats: Cell<ConflictStats>,
+    // The cursors of this session that are still open.
+    cursors: RefCell<Vec<raw_api::CursorHandle>>,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Connection, Error};
+    use super::{
+        CompareStatus, Connection, CreateConfig, DropConfig, Error, ErrorKind, IsolationLevel,
+        OpenConnectionConfig, OpenSessionConfig, StatisticsOption, Timestamp, WtValue,
+    };
+    use crate::format;
     use assert_ok::assert_ok;
+    use wiredtiger_sys as wtffi;
 
     // Tests that opening a database (without "create")
     // returns an error when the file does not exist.
@@ -129,13 +705,34 @@ mod tests {
     fn test_open_not_found() {
         let temp_dir = tempfile::tempdir().unwrap();
         let res = Connection::open(temp_dir.path().to_str().unwrap().into(), "");
-        if let Err(Error { code, message }) = res {
-            assert_eq!(message, "WT_TRY_SALVAGE: database corruption detected");
+        if let Err(Error { code, message, .. }) = res {
+            assert_eq!(code, wtffi::WT_TRY_SALVAGE);
+            assert_eq!(
+                message,
+                format!(
+                    "no WiredTiger database found at {}; pass create=true to initialize",
+                    temp_dir.path().display()
+                )
+            );
         } else {
             panic!("expected an error");
         }
     }
 
+    #[test]
+    fn test_open_nul_byte() {
+        let err = Connection::open("home\0dir", "create").unwrap_err();
+        assert_eq!(err.message, "\"home\\0dir\" contains a NUL byte");
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert!(sess.create("table:nul\0", "").is_err());
+    }
+
     #[test]
     fn test_basic() {
         // Create a temp dir to put the WT files into, open a connection to it.
@@ -151,15 +748,15 @@ mod tests {
             assert_ok!(create_result);
             let cur = assert_ok!(sess.open_cursor("table:mytable"));
 
-            cur.set_key("tyler");
-            cur.set_value("brock");
+            assert_ok!(cur.set_key("tyler"));
+            assert_ok!(cur.set_value("brock"));
             assert_ok!(cur.insert());
 
-            cur.set_key("mike");
-            cur.set_value("obrien");
+            assert_ok!(cur.set_key("mike"));
+            assert_ok!(cur.set_value("obrien"));
             assert_ok!(cur.insert());
 
-            cur.set_key("tyler");
+            assert_ok!(cur.set_key("tyler"));
             assert_ok!(cur.search());
 
             let (k, v) = assert_ok!(cur.get_raw_key_value());
@@ -191,47 +788,1406 @@ mod tests {
     }
 
     #[test]
-    fn test_reconfigure() {
+    fn test_list_tables() {
         let temp_dir = tempfile::tempdir().unwrap();
-        let conn = Connection::open(temp_dir.path().to_str().unwrap().into(), "create")
+        let conn = Connection::open(temp_dir.path().to_str().unwrap(), "create")
             .expect("failed to open connection");
+        {
+            let sess = assert_ok!(conn.open_session());
+            assert_ok!(sess.create("table:strings", "key_format=S,value_format=S"));
+            assert_ok!(sess.create("table:numbers", "key_format=q,value_format=u"));
+        }
+
+        let tables = assert_ok!(conn.list_tables());
+        assert_eq!(tables.len(), 2);
+
+        let (_, config) = tables
+            .iter()
+            .find(|(uri, _)| uri == "table:strings")
+            .expect("table:strings should be listed");
+        assert!(config.contains("key_format=S"));
+        assert!(config.contains("value_format=S"));
+
+        let (_, config) = tables
+            .iter()
+            .find(|(uri, _)| uri == "table:numbers")
+            .expect("table:numbers should be listed");
+        assert!(config.contains("key_format=q"));
+        assert!(config.contains("value_format=u"));
+    }
+
+    #[test]
+    fn test_open_with_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = OpenConnectionConfig::builder()
+            .create(true)
+            .cache_size_mb(512)
+            .statistics(&[StatisticsOption::Fast])
+            .build();
+        let conn = assert_ok!(Connection::open_with(
+            temp_dir.path().to_str().unwrap(),
+            &config
+        ));
         let sess = assert_ok!(conn.open_session());
 
-        // Calling connection reconfigure with an invalid config string fails
-        assert!(matches!(
-            conn.reconfigure("bogus"),
-            Err(Error {
-                code,
-                message,
-            })
-            if message == "Invalid argument" && code == libc::EINVAL
+        // Statistics are only available because of the config, and report the
+        // configured cache size.
+        let stats = assert_ok!(sess.statistics("", ""));
+        let (_, cache_size) = stats
+            .iter()
+            .find(|(desc, _)| desc == "cache: maximum bytes configured")
+            .expect("cache size should be reported");
+        assert_eq!(*cache_size, 512 << 20);
+    }
+
+    #[test]
+    fn test_open_in_memory() {
+        let conn = assert_ok!(Connection::open_in_memory("cache_size=10MB"));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:mem", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:mem"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("v"));
+        assert_ok!(cur.insert());
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).1, Some(b"v".to_vec()));
+        assert!(!std::path::Path::new("WiredTiger").exists());
+    }
+
+    #[test]
+    fn test_open_with_cache_overhead() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = assert_ok!(OpenConnectionConfig::builder()
+            .create(true)
+            .cache_overhead(12))
+        .build();
+        let conn = assert_ok!(Connection::open_with(
+            temp_dir.path().to_str().unwrap(),
+            &config
         ));
+        assert_eq!(conn.config(), "cache_overhead=12,create=true");
 
-        // Calling session reconfigure with an invalid config string fails
-        assert!(matches!(
-            sess.reconfigure("bogus"),
-            Err(Error {
-                code,
-                message,
-            })
-            if message == "Invalid argument" && code == libc::EINVAL
+        // WiredTiger records the settings a database was created with in its
+        // base config file.
+        let base = std::fs::read_to_string(temp_dir.path().join("WiredTiger.basecfg")).unwrap();
+        assert!(base.contains("cache_overhead=12"));
+
+        // And checks the value itself, not only the builder.
+        let other_dir = tempfile::tempdir().unwrap();
+        assert!(Connection::open(
+            other_dir.path().to_str().unwrap(),
+            "create,cache_overhead=31"
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_insert_span() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+
+        type SpanFields = Vec<(String, String)>;
+
+        // Records the name and fields of every span created.
+        #[derive(Clone, Default)]
+        struct Spans(Arc<Mutex<Vec<(String, SpanFields)>>>);
+
+        struct Fields(SpanFields);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .push((field.name().to_string(), format!("{:?}", value)));
+            }
+        }
+
+        impl tracing::Subscriber for Spans {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut fields = Fields(Vec::new());
+                span.record(&mut fields);
+                let mut spans = self.0.lock().unwrap();
+                spans.push((span.metadata().name().to_string(), fields.0));
+                Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &tracing::Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
         ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:traced", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:traced"));
+        let spans = Spans::default();
+        tracing::subscriber::with_default(spans.clone(), || {
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        });
+        let spans = spans.0.lock().unwrap();
+        assert_eq!(
+            *spans,
+            [(
+                "insert".to_string(),
+                vec![("uri".to_string(), "table:traced".to_string())]
+            )]
+        );
+    }
 
-        // Calling cursor reconfigure with an invalid config string fails
-        assert_ok!(sess.create("table:foo", ""));
-        let cur = assert_ok!(sess.open_cursor("table:foo"));
-        assert!(matches!(
-            cur.reconfigure("bogus"),
-            Err(Error {
-                code,
-                message,
+    #[test]
+    fn test_get_home_and_is_new() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        {
+            let conn = assert_ok!(Connection::open(home, "create"));
+            assert!(conn.is_new());
+            assert_eq!(assert_ok!(conn.get_home()), home);
+        }
+
+        let conn = assert_ok!(Connection::open(home, "create"));
+        assert!(!conn.is_new());
+        assert_eq!(assert_ok!(conn.get_home()), home);
+        // Clones share the connection, and so its home.
+        assert_eq!(assert_ok!(conn.clone().get_home()), home);
+    }
+
+    #[test]
+    fn test_close_with_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        let conn = assert_ok!(Connection::open(home, "create"));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:closed", "key_format=S,value_format=S"));
+        // The session still shares the connection, so it's left for the session
+        // to close when it's dropped.
+        assert!(conn.close("").is_err());
+        drop(sess);
+
+        let conn = assert_ok!(Connection::open(home, ""));
+        assert_ok!(conn.close("leak_memory=true"));
+
+        // It was a clean shutdown.
+        let conn = assert_ok!(Connection::open(home, ""));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.open_cursor("table:closed"));
+    }
+
+    // Counts WiredTiger's messages, which with `verbose=[checkpoint]` come from
+    // its checkpoints.
+    struct MessageCount(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl super::EventHandler for MessageCount {
+        fn on_error(&self, _code: i32, _message: &str) {}
+
+        fn on_message(&self, _message: &str) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+
+        fn on_progress(&self, _operation: &str, _progress: u64) {}
+    }
+
+    // Writes a row and drops the connection, returning how many checkpoint
+    // messages WiredTiger sent from opening until it was closed.
+    fn write_and_drop(home: &str, checkpoint_on_close: bool) -> usize {
+        let messages = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        {
+            let mut conn = assert_ok!(Connection::open_with_handler(
+                home,
+                "create,verbose=[checkpoint]",
+                Box::new(MessageCount(messages.clone()))
+            ));
+            if checkpoint_on_close {
+                conn = conn.with_checkpoint_on_close();
+            }
+            let sess = assert_ok!(conn.open_session());
+            assert_ok!(sess.create("table:durable", "key_format=S,value_format=S"));
+            let cur = assert_ok!(sess.open_cursor("table:durable"));
+            assert_ok!(cur.set_key("last"));
+            assert_ok!(cur.set_value("write"));
+            assert_ok!(cur.insert());
+        }
+        messages.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_checkpoint_on_close() {
+        // Closing always checkpoints, so the flag shows up as one checkpoint more.
+        let (plain_dir, flagged_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let plain = write_and_drop(plain_dir.path().to_str().unwrap(), false);
+        let home = flagged_dir.path().to_str().unwrap();
+        let flagged = write_and_drop(home, true);
+        assert!(
+            flagged > plain,
+            "{} messages with the flag, {} without",
+            flagged,
+            plain
+        );
+
+        let conn = assert_ok!(Connection::open(home, ""));
+        let sess = assert_ok!(conn.open_session());
+        let cur = assert_ok!(sess.open_cursor("table:durable"));
+        assert_ok!(cur.set_key("last"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_value()),
+            [WtValue::Str("write".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_on_close_failure() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        drop(assert_ok!(Connection::open(home, "create")));
+
+        // A read-only connection can't checkpoint. Dropping it still closes it
+        // rather than panicking, while `close` reports the failure.
+        drop(assert_ok!(Connection::open(home, "readonly=true")).with_checkpoint_on_close());
+        let conn = assert_ok!(Connection::open(home, "readonly=true")).with_checkpoint_on_close();
+        assert!(conn.close("").is_err());
+
+        // Both were closed, or the database couldn't be opened again.
+        assert_ok!(Connection::open(home, ""));
+    }
+
+    // Needs WiredTiger's `dir_store` storage source, which stands in for a
+    // bucket with a local directory; `WIREDTIGER_DIR_STORE` is the path to its
+    // shared library.
+    #[cfg(feature = "tiered")]
+    #[test]
+    fn test_flush_tier() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join("bucket")).unwrap();
+        let extension = std::env::var("WIREDTIGER_DIR_STORE")
+            .expect("WIREDTIGER_DIR_STORE should be the path to libwiredtiger_dir_store.so");
+        let config = OpenConnectionConfig::builder()
+            .create(true)
+            .tiered_storage(super::TieredStorageConfig {
+                name: "dir_store".to_string(),
+                bucket: "bucket".to_string(),
+                bucket_prefix: "pfx-".to_string(),
+                ..Default::default()
             })
-            if message == "Invalid argument" && code == libc::EINVAL
+            .build();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            &format!("{},extensions=[{:?}]", config, extension)
         ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:tiered", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:tiered"));
+        assert_ok!(cur.set_key("key"));
+        assert_ok!(cur.set_value("value"));
+        assert_ok!(cur.insert());
+        assert_ok!(sess.checkpoint(""));
+        assert_ok!(sess.flush_tier(""));
 
-        // Reconfigure with valid args is successful
-        assert_ok!(sess.reconfigure("cache_max_wait_ms=12"));
-        assert_ok!(conn.reconfigure("eviction_target=75"));
-        assert_ok!(cur.reconfigure("append=true"));
+        let flushed = std::fs::read_dir(temp_dir.path().join("bucket")).unwrap();
+        assert!(flushed
+            .map(|entry| entry.unwrap().file_name())
+            .any(|name| name.to_string_lossy().starts_with("pfx-")));
+    }
+
+    #[test]
+    fn test_close_session() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:session", "key_format=S,value_format=S"));
+        // Dropping the closed session doesn't close it again, which would fail.
+        assert_ok!(sess.close());
+        assert_ok!(conn.close(""));
+    }
+
+    #[test]
+    fn test_open_session_with() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        assert!(conn.open_session_with("isolation=serializable").is_err());
+        let writer = assert_ok!(conn.open_session());
+        assert_ok!(writer.create("table:isolated", "key_format=S,value_format=S"));
+        let config = OpenSessionConfig {
+            isolation: Some(IsolationLevel::Snapshot),
+            cache_cursors: false,
+        };
+        let reader = assert_ok!(conn.open_session_with(&config.to_string()));
+        let reader_cur = assert_ok!(reader.open_cursor("table:isolated"));
+        let writer_cur = assert_ok!(writer.open_cursor("table:isolated"));
+
+        // A snapshot transaction keeps reading what was committed when it began.
+        assert_ok!(reader.begin_transaction(""));
+        assert_ok!(reader_cur.set_key("k"));
+        assert!(reader_cur.search().is_err());
+        assert_ok!(writer_cur.set_key("k"));
+        assert_ok!(writer_cur.set_value("v"));
+        assert_ok!(writer_cur.insert());
+        assert_ok!(reader_cur.set_key("k"));
+        assert!(reader_cur.search().is_err());
+        assert_ok!(reader.rollback_transaction(""));
+
+        assert_ok!(reader_cur.set_key("k"));
+        assert_ok!(reader_cur.search());
+    }
+
+    #[test]
+    fn test_insert_no_overwrite() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:no_overwrite", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor_no_overwrite("table:no_overwrite"));
+
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("first"));
+        assert!(assert_ok!(cur.insert()));
+
+        // The second insert reports the key as already present and keeps the
+        // original value.
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("second"));
+        assert!(!assert_ok!(cur.insert()));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_raw_key_value()).1,
+            Some(b"first".to_vec())
+        );
+
+        // Updating a missing key fails, and an existing one is updated.
+        assert_ok!(cur.set_key("missing"));
+        assert_ok!(cur.set_value("value"));
+        assert!(cur.update().unwrap_err().is_not_found());
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("updated"));
+        assert_ok!(cur.update());
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_raw_key_value()).1,
+            Some(b"updated".to_vec())
+        );
+
+        // A default cursor overwrites instead.
+        let cur = assert_ok!(sess.open_cursor("table:no_overwrite"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("second"));
+        assert!(assert_ok!(cur.insert()));
+    }
+
+    #[test]
+    fn test_create_with_data_type() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        for data_type in ["file", "lsm"] {
+            let config = assert_ok!(CreateConfig::builder()
+                .key_format("S")
+                .value_format("S")
+                .data_type(data_type))
+            .build();
+            let table = format!("table:{}_table", data_type);
+            assert_ok!(sess.create_with(&table, &config));
+
+            // The table's column group records where it's stored.
+            let colgroup = format!("colgroup:{}_table", data_type);
+            let entries = assert_ok!(sess.list_by_type(&colgroup));
+            let (_, metadata) = entries.first().expect("expected a column group");
+            assert!(metadata.contains(&format!("type={}", data_type)));
+        }
+    }
+
+    #[test]
+    fn test_create_with_mismatched_columns() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let people = |columns: &[&str]| {
+            CreateConfig::builder()
+                .key_format("S")
+                .value_format("Si")
+                .columns(columns)
+                .build()
+        };
+        assert_ok!(sess.create_with("table:people", &people(&["id", "name", "age"])));
+
+        let err = sess
+            .create_with("table:mismatched", &people(&["id", "name"]))
+            .unwrap_err();
+        assert!(err.message.contains("value_format \"Si\" has 2 field(s)"));
+        assert!(assert_ok!(conn.list_tables())
+            .iter()
+            .all(|(uri, _)| uri != "table:mismatched"));
+    }
+
+    #[test]
+    fn test_create_with_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create_with("table:default", &CreateConfig::default()));
+        assert_ok!(sess.create_with(
+            "table:people",
+            &CreateConfig::builder()
+                .key_format("S")
+                .value_format("Si")
+                .columns(&["name", "city", "age"])
+                .build()
+        ));
+        assert_ok!(sess.create_with(
+            "table:split",
+            &CreateConfig::builder()
+                .key_format("q")
+                .value_format("SS")
+                .columns(&["id", "first", "last"])
+                .colgroups(&["names"])
+                .prefix_compression(true)
+                .build()
+        ));
+
+        let tables = assert_ok!(conn.list_tables());
+        let config = |uri: &str| {
+            tables
+                .iter()
+                .find(|(name, _)| name == uri)
+                .map(|(_, config)| config.clone())
+                .unwrap()
+        };
+        let default = config("table:default");
+        assert!(default.contains("key_format=u"));
+        assert!(default.contains("value_format=u"));
+        let people = config("table:people");
+        assert!(people.contains("columns=(name,city,age)"));
+        assert!(people.contains("key_format=S"));
+        assert!(people.contains("value_format=Si"));
+        let split = config("table:split");
+        assert!(split.contains("colgroups=(names)"));
+        assert!(split.contains("key_format=q"));
+        assert!(split.contains("value_format=SS"));
+    }
+
+    #[test]
+    fn test_create_with_allocation_size() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let config = assert_ok!(CreateConfig::builder()
+            .key_format("S")
+            .value_format("S")
+            .allocation_size(512))
+        .build();
+        assert_ok!(sess.create_with("table:small_blocks", &config));
+
+        let metadata = assert_ok!(sess.open_cursor("metadata:"));
+        assert_ok!(metadata.set_key("file:small_blocks.wt"));
+        assert_ok!(metadata.search());
+        let (_, file_config) = assert_ok!(metadata.get_raw_key_value());
+        let file_config = String::from_utf8(file_config.unwrap()).unwrap();
+        assert!(file_config.contains("allocation_size=512"));
+
+        let err = CreateConfig::builder().allocation_size(3000).err().unwrap();
+        assert!(err.message.contains("power of two"));
+    }
+
+    #[test]
+    fn test_transaction_commit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:txn", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:txn"));
+
+        assert_ok!(sess.with_transaction("", |_| {
+            cur.set_key("committed")?;
+            cur.set_value("yes")?;
+            cur.insert()
+        }));
+
+        assert!(sess
+            .with_transaction("", |_| {
+                cur.set_key("rolled back")?;
+                cur.set_value("no")?;
+                cur.insert()?;
+                Err::<(), _>(Error::new("roll back"))
+            })
+            .is_err());
+
+        assert_ok!(cur.set_key("committed"));
+        assert_ok!(cur.search());
+        assert_ok!(cur.set_key("rolled back"));
+        assert!(cur.search().unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_drop_with_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+
+        // Dropping a table that doesn't exist fails by default, but not when forced.
+        assert!(sess
+            .drop_with("table:missing", &DropConfig::default())
+            .is_err());
+        let force = DropConfig::builder().force(true).build();
+        assert_ok!(sess.drop_with("table:missing", &force));
+
+        // Keeping the files leaves the .wt file behind after the drop.
+        assert_ok!(sess.create("table:kept", "key_format=S,value_format=S"));
+        let keep_files = DropConfig::builder().remove_files(false).build();
+        assert_ok!(sess.drop_with("table:kept", &keep_files));
+        assert!(sess.open_cursor("table:kept").is_err());
+        assert!(temp_dir.path().join("kept.wt").exists());
+    }
+
+    #[test]
+    fn test_create_in() {
+        let parent = tempfile::tempdir().unwrap();
+        for name in ["first", "second"] {
+            let conn = assert_ok!(Connection::create_in(parent.path(), name, ""));
+            let sess = assert_ok!(conn.open_session());
+            assert_ok!(sess.create(&format!("table:{}", name), "key_format=S,value_format=S"));
+        }
+
+        // Each database has only its own table.
+        for name in ["first", "second"] {
+            assert!(parent.path().join(name).join("WiredTiger").exists());
+            let conn = assert_ok!(Connection::create_in(
+                parent.path(),
+                name,
+                "cache_size=10MB"
+            ));
+            assert!(!conn.is_new());
+            let tables: Vec<String> = assert_ok!(conn.list_tables())
+                .into_iter()
+                .map(|(uri, _)| uri)
+                .collect();
+            assert_eq!(tables, [format!("table:{}", name)]);
+        }
+    }
+
+    #[test]
+    fn test_index_cursor_and_projection() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create(
+            "table:people",
+            "key_format=S,value_format=SSi,columns=(id,name,city,age)"
+        ));
+        assert_ok!(sess.create("index:people:name", "columns=(name)"));
+        {
+            // The values are packed "SSi"; 30 packs to the single byte 0x80 | 30.
+            let cur = assert_ok!(sess.open_cursor_with_config("table:people", "raw"));
+            assert_ok!(cur.raw_cursor.set_key_item(b"p1\0"));
+            assert_ok!(cur.raw_cursor.set_value_item(b"alice\0nyc\0\x9e"));
+            assert_ok!(cur.insert());
+        }
+
+        let by_name = assert_ok!(sess.open_index_cursor("people", "name"));
+        assert_ok!(by_name.set_key("alice"));
+        assert_ok!(by_name.search());
+        assert_eq!(
+            assert_ok!(by_name.get_value()),
+            [
+                WtValue::Str("alice".to_string()),
+                WtValue::Str("nyc".to_string()),
+                WtValue::Int(30)
+            ]
+        );
+
+        let projected = assert_ok!(sess.open_cursor("table:people(age,city)"));
+        assert_ok!(projected.set_key("p1"));
+        assert_ok!(projected.search());
+        assert_eq!(
+            assert_ok!(projected.get_value()),
+            [WtValue::Int(30), WtValue::Str("nyc".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_list_by_type() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create(
+            "table:people",
+            "key_format=S,value_format=S,columns=(id,name)"
+        ));
+        assert_ok!(sess.create("index:people:name", "columns=(name)"));
+
+        let indices = assert_ok!(sess.list_by_type("index:"));
+        assert_eq!(indices.len(), 1);
+        let (uri, config) = &indices[0];
+        assert_eq!(uri, "index:people:name");
+        assert!(config.contains("columns=(name"));
+        assert_eq!(assert_ok!(sess.list_by_type("table:")).len(), 1);
+    }
+
+    #[test]
+    fn test_reset_all_cursors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:reset", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:reset"));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        }
+        assert_eq!(sess.open_cursor_count(), 0);
+
+        let cursors: Vec<_> = (0..3)
+            .map(|_| assert_ok!(sess.open_cursor("table:reset")))
+            .collect();
+        for cur in &cursors {
+            assert_ok!(cur.next());
+        }
+        assert_eq!(sess.open_cursor_count(), 3);
+
+        assert_ok!(sess.reset_all_cursors());
+        for cur in &cursors {
+            assert!(cur.get_raw_key_value().is_err());
+        }
+        drop(cursors);
+        assert_eq!(sess.open_cursor_count(), 0);
+    }
+
+    #[test]
+    fn test_key_and_value_bytes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:bytes", "key_format=S,value_format=u"));
+        let mut cur = assert_ok!(sess.open_cursor("table:bytes"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.raw_cursor.set_value_item(b"\x00\xff"));
+        assert_ok!(cur.insert());
+
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        // Nothing else touches the cursor while the bytes are held.
+        assert_eq!(assert_ok!(unsafe { cur.key_bytes() }), b"k\0");
+        assert_eq!(assert_ok!(unsafe { cur.value_bytes() }), b"\x00\xff");
+    }
+
+    #[test]
+    fn test_get_display_value() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:mixed", "key_format=S,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:mixed"));
+        for (key, value) in [
+            ("text", "héllo".as_bytes()),
+            ("binary", &[0xff, 0x00, 0x10]),
+        ] {
+            assert_ok!(cur.set_key(key));
+            assert_ok!(cur.raw_cursor.set_value_item(value));
+            assert_ok!(cur.insert());
+        }
+
+        assert_ok!(cur.set_key("text"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_display_value()), "héllo");
+        assert_ok!(cur.set_key("binary"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_display_value()), "0xff0010");
+    }
+
+    #[test]
+    fn test_cursor_formats() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:counts", "key_format=S,value_format=q"));
+        let cur = assert_ok!(sess.open_cursor("table:counts"));
+        assert_eq!(cur.key_format(), "S");
+        assert_eq!(cur.value_format(), "q");
+        // String values don't fit a `q` table.
+        assert_eq!(
+            cur.set_value("one").unwrap_err().kind,
+            ErrorKind::FormatMismatch
+        );
+    }
+
+    #[test]
+    fn test_get_key_value() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:typed", "key_format=i,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor_with_config("table:typed", "raw"));
+            for (key, value) in [(-5, "minus five"), (7, "seven")] {
+                let key = assert_ok!(format::pack("i", &[WtValue::Int(key)]));
+                let value = assert_ok!(format::pack("S", &[WtValue::Str(value.to_string())]));
+                assert_ok!(cur.raw_cursor.set_key_item(&key));
+                assert_ok!(cur.raw_cursor.set_value_item(&value));
+                assert_ok!(cur.insert());
+            }
+        }
+
+        let cur = assert_ok!(sess.open_cursor("table:typed"));
+        let mut pairs: Vec<(i64, String)> = Vec::new();
+        while cur.next().is_ok() {
+            pairs.push(assert_ok!(cur.get_key_value()));
+        }
+        assert_eq!(
+            pairs,
+            [(-5, "minus five".to_string()), (7, "seven".to_string())]
+        );
+        // The key is an `i`, which doesn't decode as a String.
+        assert_ok!(cur.prev());
+        let err = cur.get_key_value::<String, String>().unwrap_err();
+        assert_eq!(err.kind, ErrorKind::FormatMismatch);
+    }
+
+    #[test]
+    fn test_append() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:log", "key_format=r,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor_with_config("table:log", "append"));
+        let recnos: Vec<u64> = ["first", "second", "third"]
+            .iter()
+            .map(|value| assert_ok!(cur.append(value.as_bytes())))
+            .collect();
+        assert_eq!(recnos, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_search_near_full() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:near", "key_format=u,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:near"));
+        assert_eq!(assert_ok!(cur.search_near_full(b"b")), None);
+
+        for (key, value) in [(b"b", b"2"), (b"d", b"4")] {
+            assert_ok!(cur.raw_cursor.set_key_item(key));
+            assert_ok!(cur.raw_cursor.set_value_item(value));
+            assert_ok!(cur.insert());
+        }
+
+        assert_eq!(
+            assert_ok!(cur.search_near_full(b"b")),
+            Some((CompareStatus::Equal, b"b".to_vec(), b"2".to_vec()))
+        );
+        // Nothing comes before "a", so the next key up is found.
+        assert_eq!(
+            assert_ok!(cur.search_near_full(b"a")),
+            Some((CompareStatus::GreaterThan, b"b".to_vec(), b"2".to_vec()))
+        );
+        // Nor after "e", so the next key down is.
+        assert_eq!(
+            assert_ok!(cur.search_near_full(b"e")),
+            Some((CompareStatus::LessThan, b"d".to_vec(), b"4".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_largest_key_bytes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:recnos", "key_format=r,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor_with_config("table:recnos", "append"));
+        assert_eq!(assert_ok!(cur.largest_key_bytes()), None);
+
+        for value in ["a", "b", "c"] {
+            assert_ok!(cur.append(value.as_bytes()));
+        }
+        let key = assert_ok!(cur.largest_key_bytes()).unwrap();
+        assert_eq!(assert_ok!(format::unpack("r", &key)), [WtValue::UInt(3)]);
+    }
+
+    #[test]
+    fn test_stable_timestamp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        assert_ok!(conn.set_timestamp(&format!(
+            "oldest_timestamp={},stable_timestamp={}",
+            Timestamp(0x10),
+            Timestamp(0x2a)
+        )));
+        assert_eq!(
+            assert_ok!(conn.query_timestamp("get=stable_timestamp")),
+            Timestamp(0x2a)
+        );
+        assert_eq!(
+            assert_ok!(conn.query_timestamp("get=oldest_timestamp")),
+            Timestamp(0x10)
+        );
+    }
+
+    #[test]
+    fn test_truncate_database() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        {
+            let sess = assert_ok!(conn.open_session());
+            for uri in ["table:a", "table:b", "table:c"] {
+                assert_ok!(sess.create(uri, "key_format=S,value_format=S"));
+                let cur = assert_ok!(sess.open_cursor(uri));
+                assert_ok!(cur.set_key("k"));
+                assert_ok!(cur.set_value("v"));
+                assert_ok!(cur.insert());
+            }
+            assert_ok!(sess.checkpoint("name=populated"));
+        }
+
+        assert_ok!(conn.truncate_database());
+        assert!(assert_ok!(conn.list_tables()).is_empty());
+        assert!(!temp_dir.path().join("a.wt").exists());
+
+        // The connection is still usable, and the names are free again.
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:a", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:a"));
+        assert_ok!(cur.set_key("k"));
+        assert!(cur.search().unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_truncate_table() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:emptied", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:emptied"));
+            for i in 0..1000 {
+                assert_ok!(cur.set_key(&format!("{:04}", i)));
+                assert_ok!(cur.set_value("v"));
+                assert_ok!(cur.insert());
+            }
+        }
+
+        assert_ok!(sess.truncate_table("table:emptied"));
+        let cur = assert_ok!(sess.open_cursor("table:emptied"));
+        assert!(cur.next().unwrap_err().is_not_found());
+        let tables: Vec<String> = assert_ok!(conn.list_tables())
+            .into_iter()
+            .map(|(uri, _)| uri)
+            .collect();
+        assert_eq!(tables, ["table:emptied"]);
+        assert!(sess.truncate_table("table:missing").is_err());
+    }
+
+    #[test]
+    fn test_log_flush_and_printf() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create,log=(enabled=true)"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.log_printf("application marker: 100% done"));
+        assert_ok!(sess.log_flush("sync=on"));
+    }
+
+    #[test]
+    fn test_rollback_to_stable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:rts", "key_format=S,value_format=S"));
+        assert_ok!(conn.set_timestamp(&format!("oldest_timestamp={}", Timestamp(1))));
+        for (key, ts) in [("stable", 5), ("unstable", 20)] {
+            let cur = assert_ok!(sess.open_cursor("table:rts"));
+            let mut txn = assert_ok!(sess.transaction(""));
+            assert_ok!(cur.set_key(key));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+            assert_ok!(txn.commit_with_timestamp(Timestamp(ts)));
+        }
+
+        assert_ok!(conn.set_timestamp(&format!("stable_timestamp={}", Timestamp(10))));
+        assert_ok!(conn.rollback_to_stable(""));
+
+        let cur = assert_ok!(sess.open_cursor("table:rts"));
+        assert_ok!(cur.set_key("stable"));
+        assert_ok!(cur.search());
+        assert_ok!(cur.set_key("unstable"));
+        assert!(cur.search().unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_reserve_for_update() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let first = assert_ok!(conn.open_session());
+        let second = assert_ok!(conn.open_session());
+        assert_ok!(first.create("table:reserved", "key_format=u,value_format=S"));
+        let first_cur = assert_ok!(first.open_cursor("table:reserved"));
+        let second_cur = assert_ok!(second.open_cursor("table:reserved"));
+        assert_ok!(first_cur.raw_cursor.set_key_item(b"k"));
+        assert_ok!(first_cur.set_value("initial"));
+        assert_ok!(first_cur.insert());
+
+        // The first transaction reserves the record before the second writes it,
+        // so the second is the one that conflicts.
+        assert_ok!(first.begin_transaction(""));
+        assert_ok!(second.begin_transaction(""));
+        assert_ok!(first_cur.reserve_for_update(b"k"));
+        assert_ok!(second_cur.raw_cursor.set_key_item(b"k"));
+        assert_ok!(second_cur.set_value("second"));
+        assert_eq!(second_cur.update().unwrap_err().code, wtffi::WT_ROLLBACK);
+        assert_ok!(second.rollback_transaction(""));
+
+        assert_ok!(first_cur.set_value("first"));
+        assert_ok!(first_cur.update());
+        assert_ok!(first.commit_transaction(""));
+        assert_ok!(second_cur.raw_cursor.set_key_item(b"k"));
+        assert_ok!(second_cur.search());
+        assert_eq!(
+            assert_ok!(second_cur.get_value()),
+            [WtValue::Str("first".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_last_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let first = assert_ok!(conn.open_session());
+        let second = assert_ok!(conn.open_session());
+        assert_ok!(first.create("table:conflict", "key_format=S,value_format=S"));
+        let first_cur = assert_ok!(first.open_cursor("table:conflict"));
+        let second_cur = assert_ok!(second.open_cursor("table:conflict"));
+
+        assert_ok!(first.begin_transaction(""));
+        assert_ok!(second.begin_transaction(""));
+        assert_ok!(first_cur.set_key("k"));
+        assert_ok!(first_cur.set_value("first"));
+        assert_ok!(first_cur.insert());
+
+        // Both transactions writing the same key is a conflict, which WiredTiger
+        // reports as a rollback with a write conflict sub-level code.
+        assert_ok!(second_cur.set_key("k"));
+        assert_ok!(second_cur.set_value("second"));
+        let err = second_cur.insert().unwrap_err();
+        assert_eq!(err.code, wtffi::WT_ROLLBACK);
+        let last = second.last_error();
+        assert_eq!(last.err, wtffi::WT_ROLLBACK);
+        assert_eq!(last.sub_level, wtffi::WT_WRITE_CONFLICT);
+        assert!(!last.message.is_empty());
+
+        assert_ok!(second.rollback_transaction(""));
+        assert_ok!(first.commit_transaction(""));
+    }
+
+    #[test]
+    fn test_transaction_pinned_range() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let reader = assert_ok!(conn.open_session());
+        let writer = assert_ok!(conn.open_session());
+        assert_ok!(writer.create("table:pinned", "key_format=S,value_format=S"));
+
+        assert_ok!(reader.begin_transaction(""));
+        let read_cur = assert_ok!(reader.open_cursor("table:pinned"));
+        assert_ok!(read_cur.set_key("k"));
+        assert!(read_cur.search().unwrap_err().is_not_found());
+        let before = assert_ok!(reader.transaction_pinned_range());
+
+        // Commits made while the reader's snapshot is open widen what it pins.
+        let write_cur = assert_ok!(writer.open_cursor("table:pinned"));
+        for i in 0..10 {
+            assert_ok!(write_cur.set_key(&i.to_string()));
+            assert_ok!(write_cur.set_value("v"));
+            assert_ok!(write_cur.insert());
+        }
+        assert!(assert_ok!(reader.transaction_pinned_range()) >= before);
+        assert_ok!(reader.rollback_transaction(""));
+    }
+
+    #[test]
+    fn test_set_isolation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let reader = assert_ok!(conn.open_session());
+        let writer = assert_ok!(conn.open_session());
+        assert_ok!(writer.create("table:levels", "key_format=S,value_format=S"));
+        let read_cur = assert_ok!(reader.open_cursor("table:levels"));
+        let write_cur = assert_ok!(writer.open_cursor("table:levels"));
+
+        // Whether a commit made during the reader's transaction becomes visible
+        // to it.
+        let sees_concurrent_commit = |key: &str| {
+            assert_ok!(reader.begin_transaction(""));
+            assert_ok!(read_cur.set_key(key));
+            assert!(read_cur.search().unwrap_err().is_not_found());
+            assert_ok!(read_cur.reset());
+            assert_ok!(write_cur.set_key(key));
+            assert_ok!(write_cur.set_value("v"));
+            assert_ok!(write_cur.insert());
+            assert_ok!(read_cur.set_key(key));
+            let seen = read_cur.search().is_ok();
+            assert_ok!(read_cur.reset());
+            assert_ok!(reader.rollback_transaction(""));
+            seen
+        };
+
+        assert_ok!(reader.set_isolation(IsolationLevel::ReadCommitted));
+        assert!(sees_concurrent_commit("during read-committed"));
+        assert_ok!(reader.set_isolation(IsolationLevel::Snapshot));
+        assert!(!sees_concurrent_commit("during snapshot"));
+    }
+
+    #[test]
+    fn test_transaction_isolation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let writer = assert_ok!(conn.open_session());
+        assert_ok!(writer.create("table:isolation", "key_format=S,value_format=S"));
+        let write_cur = assert_ok!(writer.open_cursor("table:isolation"));
+        assert_ok!(writer.begin_transaction(""));
+        assert_ok!(write_cur.set_key("uncommitted"));
+        assert_ok!(write_cur.set_value("v"));
+        assert_ok!(write_cur.insert());
+
+        let reader = assert_ok!(conn.open_session());
+        let read_cur = assert_ok!(reader.open_cursor("table:isolation"));
+
+        // Unlike test_transaction_commit, the write hasn't been committed yet, so
+        // only a read-uncommitted transaction can see it.
+        assert_ok!(reader.begin_transaction_with(IsolationLevel::ReadUncommitted, ""));
+        assert_ok!(read_cur.set_key("uncommitted"));
+        assert_ok!(read_cur.search());
+        assert_ok!(read_cur.reset());
+        assert_ok!(reader.rollback_transaction(""));
+
+        assert_ok!(reader.begin_transaction_with(IsolationLevel::Snapshot, ""));
+        assert_ok!(read_cur.set_key("uncommitted"));
+        assert!(read_cur.search().unwrap_err().is_not_found());
+        assert_ok!(reader.rollback_transaction(""));
+
+        assert_ok!(writer.rollback_transaction(""));
+    }
+
+    #[test]
+    fn test_drop_if_empty() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:empty", "key_format=S,value_format=S"));
+        assert_ok!(sess.create("table:full", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:full"));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        }
+
+        assert!(assert_ok!(sess.drop_if_empty("table:empty")));
+        assert!(sess.open_cursor("table:empty").is_err());
+
+        assert!(!assert_ok!(sess.drop_if_empty("table:full")));
+        let cur = assert_ok!(sess.open_cursor("table:full"));
+        assert_ok!(cur.next());
+        let (key, _) = assert_ok!(cur.get_raw_key_value());
+        assert_eq!(assert_ok!(std::str::from_utf8(&key.unwrap())), "k");
+    }
+
+    #[test]
+    fn test_set_eviction_participation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(temp_dir.path().to_str().unwrap(), "create")
+            .expect("failed to open connection");
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.set_eviction_participation(false));
+        assert_ok!(sess.set_eviction_participation(true));
+    }
+
+    #[test]
+    fn test_checkpoint_cursor() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(temp_dir.path().to_str().unwrap(), "create")
+            .expect("failed to open connection");
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:mytable", "key_format=S,value_format=S"));
+
+        let cur = assert_ok!(sess.open_cursor("table:mytable"));
+        assert_ok!(cur.set_key("tyler"));
+        assert_ok!(cur.set_value("brock"));
+        assert_ok!(cur.insert());
+        assert_ok!(cur.reset());
+
+        assert_ok!(sess.checkpoint("name=ckpt1"));
+
+        assert_ok!(cur.set_key("tyler"));
+        assert_ok!(cur.set_value("obrien"));
+        assert_ok!(cur.update());
+        assert_ok!(cur.reset());
+
+        // The checkpoint cursor still sees the value as of the checkpoint.
+        let ckpt_cur = assert_ok!(sess.open_checkpoint_cursor("table:mytable", "ckpt1"));
+        assert_ok!(ckpt_cur.set_key("tyler"));
+        assert_ok!(ckpt_cur.search());
+        let (_, v) = assert_ok!(ckpt_cur.get_raw_key_value());
+        assert_eq!(assert_ok!(std::str::from_utf8(&v.unwrap())), "brock");
+
+        // The checkpoint can't be dropped out from under an open cursor.
+        assert!(sess.checkpoint("drop=(ckpt1)").is_err());
+
+        assert_ok!(cur.set_key("tyler"));
+        assert_ok!(cur.search());
+        let (_, v) = assert_ok!(cur.get_raw_key_value());
+        assert_eq!(assert_ok!(std::str::from_utf8(&v.unwrap())), "obrien");
+
+        assert!(sess
+            .open_checkpoint_cursor("table:mytable", "ckpt1,bogus=1")
+            .is_err());
+    }
+
+    // Loads the snappy compressor extension, if it was built, and uses it for a table.
+    // Point WIREDTIGER_SNAPPY_EXTENSION at libwiredtiger_snappy.so to override the
+    // default location in the vendored build tree.
+    #[test]
+    fn test_load_extension() {
+        let ext = std::env::var_os("WIREDTIGER_SNAPPY_EXTENSION")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| {
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(
+                    "wiredtiger-sys/wiredtiger/build/ext/compressors/snappy/libwiredtiger_snappy.so",
+                )
+            });
+        if !ext.exists() {
+            return;
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(temp_dir.path().to_str().unwrap(), "create")
+            .expect("failed to open connection");
+        assert_ok!(conn.load_extension(ext.to_str().unwrap(), ""));
+
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create(
+            "table:compressed",
+            "key_format=S,value_format=S,block_compressor=snappy"
+        ));
+        let cur = assert_ok!(sess.open_cursor("table:compressed"));
+        assert_ok!(cur.set_key("tyler"));
+        assert_ok!(cur.set_value("brock"));
+        assert_ok!(cur.insert());
+    }
+
+    #[test]
+    fn test_format_mismatch() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(temp_dir.path().to_str().unwrap(), "create")
+            .expect("failed to open connection");
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:recnos", "key_format=r,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:recnos"));
+
+        // A string key on a record number table is rejected before reaching WiredTiger.
+        let err = cur.set_key("tyler").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::FormatMismatch);
+        assert!(err.message.contains("key_format is \"r\""));
+        assert!(err.message.contains("\"S\" key was supplied"));
+
+        // The value format does match.
+        assert_ok!(cur.set_value("brock"));
+
+        // A raw cursor only takes packed items, even when the format is "S".
+        assert_ok!(sess.create("table:strings", "key_format=S,value_format=S"));
+        let raw = assert_ok!(sess.open_cursor_with_config("table:strings", "raw"));
+        let err = raw.set_key("tyler").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::FormatMismatch);
+        assert!(err.message.contains("opened with raw"));
+        assert_eq!(
+            raw.set_value("brock").unwrap_err().kind,
+            ErrorKind::FormatMismatch
+        );
+        assert_ok!(raw.raw_cursor.set_key_item(b"tyler\0"));
+    }
+
+    #[test]
+    fn test_reconfigure() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(temp_dir.path().to_str().unwrap().into(), "create")
+            .expect("failed to open connection");
+        let sess = assert_ok!(conn.open_session());
+
+        // Calling connection reconfigure with an invalid config string fails
+        assert!(matches!(
+            conn.reconfigure("bogus"),
+            Err(Error {
+                code,
+                message,
+                ..
+            })
+            if message == "Invalid argument" && code == libc::EINVAL
+        ));
+
+        // Calling session reconfigure with an invalid config string fails
+        assert!(matches!(
+            sess.reconfigure("bogus"),
+            Err(Error {
+                code,
+                message,
+                ..
+            })
+            if message == "Invalid argument" && code == libc::EINVAL
+        ));
+
+        // Calling cursor reconfigure with an invalid config string fails
+        assert_ok!(sess.create("table:foo", ""));
+        let cur = assert_ok!(sess.open_cursor("table:foo"));
+        assert!(matches!(
+            cur.reconfigure("bogus"),
+            Err(Error {
+                code,
+                message,
+                ..
+            })
+            if message == "Invalid argument" && code == libc::EINVAL
+        ));
+
+        // Reconfigure with valid args is successful
+        assert_ok!(sess.reconfigure("cache_max_wait_ms=12"));
+        assert_ok!(conn.reconfigure("eviction_target=75"));
+        assert_ok!(cur.reconfigure("append=true"));
+    }
+
+    #[test]
+    fn test_sessions_on_threads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:threads", "key_format=S,value_format=S"));
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let conn = conn.clone();
+                std::thread::spawn(move || {
+                    let sess = assert_ok!(conn.open_session());
+                    let cur = assert_ok!(sess.open_cursor("table:threads"));
+                    for i in 0..100 {
+                        assert_ok!(cur.set_key(&format!("{}-{:03}", t, i)));
+                        assert_ok!(cur.set_value("v"));
+                        assert_ok!(cur.insert());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // The session keeps the connection open after the handle is gone.
+        drop(conn);
+        let cur = assert_ok!(sess.open_cursor("table:threads"));
+        let mut count = 0;
+        while cur.next().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 400);
     }
 }
@@ -0,0 +1,70 @@
+use crate::raw_api::{Modify, Result};
+use crate::Cursor;
+
+impl<'a> Cursor<'a> {
+    /// Appends `suffix` to the value of `key` with a `modify`, so WiredTiger
+    /// records only the appended bytes rather than a new copy of the whole
+    /// value. The key must exist and the value format must be `u`. It runs in a
+    /// transaction of its own, so it mustn't be called inside one.
+    pub fn append_to_value(&self, key: &str, suffix: &[u8]) -> Result<()> {
+        let session = &self.session.raw_session;
+        session.begin_transaction("")?;
+        let result = self.append_in_transaction(key, suffix);
+        match result {
+            Ok(()) => session.commit_transaction(""),
+            Err(err) => {
+                session.rollback_transaction("")?;
+                Err(err)
+            }
+        }
+    }
+
+    fn append_in_transaction(&self, key: &str, suffix: &[u8]) -> Result<()> {
+        self.set_key(key)?;
+        self.search()?;
+        // A `u` value is packed as its bytes alone.
+        let (_, value) = self.raw_cursor.get_packed_key_value()?;
+        let append = Modify {
+            data: suffix,
+            offset: value.len(),
+            size: 0,
+        };
+        self.raw_cursor.modify(std::iter::once(append))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_append_to_value() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:blobs", "key_format=S,value_format=u"));
+        let cur = assert_ok!(sess.open_cursor("table:blobs"));
+        assert_ok!(cur.set_key("blob"));
+        assert_ok!(cur.raw_cursor.set_value_item(b""));
+        assert_ok!(cur.insert());
+
+        for chunk in [&b"one"[..], b"two", b"three"] {
+            assert_ok!(cur.append_to_value("blob", chunk));
+        }
+        assert_ok!(cur.set_key("blob"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.raw_cursor.get_packed_key_value()).1,
+            b"onetwothree"
+        );
+
+        assert!(cur
+            .append_to_value("missing", b"x")
+            .unwrap_err()
+            .is_not_found());
+    }
+}
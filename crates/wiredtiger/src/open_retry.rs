@@ -0,0 +1,108 @@
+use crate::raw_api::{Error, Result};
+use crate::{Connection, RetryPolicy};
+use std::time::Duration;
+
+impl Connection {
+    /// Opens a connection like `open`, retrying per `policy` while the home
+    /// directory is briefly unavailable (`EAGAIN`, `EBUSY`, or `ENOENT`, e.g.
+    /// while a volume is still being mounted). Any other error, such as a
+    /// database that needs salvaging, is returned straight away.
+    pub fn open_resilient(path: &str, options: &str, policy: RetryPolicy) -> Result<Self> {
+        retry_open(&policy, std::thread::sleep, || Self::open(path, options))
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    [libc::EAGAIN, libc::EBUSY, libc::ENOENT].contains(&err.code)
+}
+
+// Opens with `open` and sleeps with `sleep`, so tests can fail opens on purpose
+// without waiting.
+fn retry_open<T, S: FnMut(Duration), O: FnMut() -> Result<T>>(
+    policy: &RetryPolicy,
+    mut sleep: S,
+    mut open: O,
+) -> Result<T> {
+    let mut retry = 0;
+    loop {
+        match open() {
+            Err(err) if is_transient(&err) && (retry as usize) + 1 < policy.max_attempts => {
+                log::warn!(
+                    "retrying open after transient error: {} ({})",
+                    err.message,
+                    err.code
+                );
+                sleep(policy.backoff.delay(retry));
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::retry_open;
+    use crate::{Backoff, Error, RetryPolicy};
+    use std::time::Duration;
+    use wiredtiger_sys as wtffi;
+
+    fn error(code: i32) -> Error {
+        Error {
+            code,
+            ..Error::new("open failed")
+        }
+    }
+
+    #[test]
+    fn test_retry_open() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            backoff: Backoff::Fixed(Duration::from_millis(10)),
+        };
+
+        // Transient errors are retried until the open succeeds...
+        let mut errors = vec![libc::EBUSY, libc::ENOENT, libc::EAGAIN];
+        let mut sleeps = 0;
+        let opened = retry_open(
+            &policy,
+            |_| sleeps += 1,
+            || match errors.pop() {
+                Some(code) => Err(error(code)),
+                None => Ok("opened"),
+            },
+        );
+        assert_eq!(opened.unwrap(), "opened");
+        assert_eq!(sleeps, 3);
+
+        // ...or the policy runs out of attempts.
+        let mut attempts = 0;
+        let err = retry_open(
+            &policy,
+            |_| (),
+            || -> crate::raw_api::Result<()> {
+                attempts += 1;
+                Err(error(libc::EBUSY))
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code, libc::EBUSY);
+        assert_eq!(attempts, 5);
+
+        // Permanent errors fail on the first attempt.
+        for code in [wtffi::WT_TRY_SALVAGE, wtffi::WT_PANIC, libc::EINVAL] {
+            let mut attempts = 0;
+            let err = retry_open(
+                &policy,
+                |_| (),
+                || -> crate::raw_api::Result<()> {
+                    attempts += 1;
+                    Err(error(code))
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err.code, code);
+            assert_eq!(attempts, 1);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::raw_api::{Error, Result};
+use crate::Session;
+use std::fs::File;
+use std::path::Path;
+
+impl Session {
+    /// Asks the OS to drop its cached pages of `uri`'s file, so reads that miss
+    /// WiredTiger's own cache go to disk, e.g. for cold-cache benchmarks. Only
+    /// what has been written to the file can be dropped, so checkpoint first.
+    /// The table must be stored in a single file. Only supported on Linux.
+    pub fn evict_os_cache(&self, uri: &str) -> Result<()> {
+        let file = self.backing_file(uri)?;
+        let path = Path::new(&self.conn.get_home()?).join(file.trim_start_matches("file:"));
+        let io_error = |err: std::io::Error| Error::new(format!("{}: {}", path.display(), err));
+        let file = File::open(&path).map_err(io_error)?;
+        // Dirty pages can't be dropped until they've been written back.
+        file.sync_data().map_err(io_error)?;
+        drop_os_cache(&file).map_err(io_error)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn drop_os_cache(file: &File) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    match unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) } {
+        0 => Ok(()),
+        err => Err(std::io::Error::from_raw_os_error(err)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_os_cache(_file: &File) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "dropping the OS cache is only supported on Linux",
+    ))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    // How many of the file's pages are in the OS page cache.
+    fn resident_pages(path: &Path) -> usize {
+        let file = File::open(path).unwrap();
+        let len = file.metadata().unwrap().len() as usize;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let mut residency = vec![0u8; len.div_ceil(page_size)];
+        unsafe {
+            let addr = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            );
+            assert_ne!(addr, libc::MAP_FAILED);
+            assert_eq!(libc::mincore(addr, len, residency.as_mut_ptr()), 0);
+            libc::munmap(addr, len);
+        }
+        residency.iter().filter(|page| *page & 1 != 0).count()
+    }
+
+    // Whether `path` is on a tmpfs, whose pages live in the page cache for good.
+    fn on_tmpfs(path: &Path) -> bool {
+        let path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        let mut stats: libc::statfs = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::statfs(path.as_ptr(), &mut stats) }, 0);
+        stats.f_type == libc::TMPFS_MAGIC
+    }
+
+    #[test]
+    fn test_evict_os_cache() {
+        // Under target/, next to the test binary, rather than in /tmp, which is
+        // often a tmpfs.
+        let exe = std::env::current_exe().unwrap();
+        let temp_dir = tempfile::tempdir_in(exe.parent().unwrap()).unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        let conn = assert_ok!(Connection::open(home, "create"));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:cold", "key_format=S,value_format=S"));
+        {
+            let cur = assert_ok!(sess.open_cursor("table:cold"));
+            for i in 0..1000 {
+                assert_ok!(cur.set_key(&format!("{:04}", i)));
+                assert_ok!(cur.set_value("v"));
+                assert_ok!(cur.insert());
+            }
+        }
+        assert_ok!(sess.checkpoint(""));
+
+        // Reading the whole file brings all of it into the page cache, and
+        // evicting takes all of it out again (unless it's a tmpfs too).
+        let path = temp_dir.path().join("cold.wt");
+        std::fs::read(&path).unwrap();
+        assert!(resident_pages(&path) > 0);
+        assert_ok!(sess.evict_os_cache("table:cold"));
+        if !on_tmpfs(&path) {
+            assert_eq!(resident_pages(&path), 0);
+        }
+
+        let cur = assert_ok!(sess.open_cursor("table:cold"));
+        let mut rows = 0;
+        while cur.next().is_ok() {
+            rows += 1;
+        }
+        assert_eq!(rows, 1000);
+    }
+}
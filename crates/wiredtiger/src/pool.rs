@@ -0,0 +1,205 @@
+use crate::raw_api::Result;
+use crate::{Cursor, Session};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// Hands out cursors on a single URI, reusing previously opened cursors instead
+/// of opening a new one each time. Cursors are reset when they're returned.
+pub struct CursorPool<'a> {
+    session: &'a Session,
+    uri: String,
+    config: String,
+    // Shared with the checked out cursors, so they can return to a pool that's
+    // kept in a `CursorCache`'s map.
+    idle: Rc<RefCell<Vec<Cursor<'a>>>>,
+}
+
+/// A cursor borrowed from a `CursorPool` or `CursorCache`. Dereferences to
+/// `Cursor`, and goes back to the pool (after a `reset`) when dropped.
+pub struct PooledCursor<'p, 'a> {
+    idle: Rc<RefCell<Vec<Cursor<'a>>>>,
+    cursor: Option<Cursor<'a>>,
+    _pool: PhantomData<&'p CursorPool<'a>>,
+}
+
+/// Hands out cursors on any URI of a session, reusing the cursors returned to
+/// it: a per-session version of `CursorPool`, for code that repeatedly reads or
+/// writes a few tables.
+pub struct CursorCache<'a> {
+    session: &'a Session,
+    pools: RefCell<HashMap<String, CursorPool<'a>>>,
+}
+
+impl<'a> CursorPool<'a> {
+    pub(crate) fn new(session: &'a Session, uri: &str, config: &str) -> Self {
+        Self {
+            session,
+            uri: uri.to_string(),
+            config: config.to_string(),
+            idle: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Takes an idle cursor from the pool, or opens a new one if none are idle.
+    pub fn get(&self) -> Result<PooledCursor<'_, 'a>> {
+        self.check_out()
+    }
+
+    // Like `get`, but for any lifetime of the pool: the cursor only needs the
+    // idle list, which it shares.
+    fn check_out<'p>(&self) -> Result<PooledCursor<'p, 'a>> {
+        let idle = self.idle.borrow_mut().pop();
+        let cursor = match idle {
+            Some(cursor) => cursor,
+            None => self
+                .session
+                .open_cursor_with_config(&self.uri, &self.config)?,
+        };
+        Ok(PooledCursor {
+            idle: self.idle.clone(),
+            cursor: Some(cursor),
+            _pool: PhantomData,
+        })
+    }
+
+    /// The number of cursors currently sitting in the pool.
+    pub fn idle_count(&self) -> usize {
+        self.idle.borrow().len()
+    }
+}
+
+impl<'a> CursorCache<'a> {
+    /// Takes an idle cursor on `uri` from the cache, or opens a new one if there
+    /// are none.
+    pub fn get(&self, uri: &str) -> Result<PooledCursor<'_, 'a>> {
+        self.pools
+            .borrow_mut()
+            .entry(uri.to_string())
+            .or_insert_with(|| CursorPool::new(self.session, uri, ""))
+            .check_out()
+    }
+
+    /// The number of cursors on `uri` currently sitting in the cache.
+    pub fn idle_count(&self, uri: &str) -> usize {
+        self.pools
+            .borrow()
+            .get(uri)
+            .map_or(0, CursorPool::idle_count)
+    }
+}
+
+impl<'p, 'a> Deref for PooledCursor<'p, 'a> {
+    type Target = Cursor<'a>;
+
+    fn deref(&self) -> &Cursor<'a> {
+        self.cursor.as_ref().unwrap()
+    }
+}
+
+impl<'p, 'a> Drop for PooledCursor<'p, 'a> {
+    fn drop(&mut self) {
+        if let Some(cursor) = self.cursor.take() {
+            // A cursor that can't be reset is closed rather than handed out again.
+            if cursor.reset().is_ok() {
+                self.idle.borrow_mut().push(cursor);
+            }
+        }
+    }
+}
+
+impl Session {
+    /// Creates a pool of cursors on `uri`, each opened with `config`.
+    pub fn cursor_pool(&self, uri: &str, config: &str) -> CursorPool<'_> {
+        CursorPool::new(self, uri, config)
+    }
+
+    /// Creates a cache of cursors on any of this session's URIs.
+    pub fn cursor_cache(&self) -> CursorCache<'_> {
+        CursorCache {
+            session: self,
+            pools: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_cursor_pool() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:pooled", "key_format=S,value_format=S"));
+
+        let pool = sess.cursor_pool("table:pooled", "");
+        assert_eq!(pool.idle_count(), 0);
+
+        for (k, v) in [("a", "1"), ("b", "2"), ("c", "3")] {
+            let cur = assert_ok!(pool.get());
+            assert_ok!(cur.set_key(k));
+            assert_ok!(cur.set_value(v));
+            assert_ok!(cur.insert());
+            drop(cur);
+            // The one cursor keeps getting reused.
+            assert_eq!(pool.idle_count(), 1);
+        }
+
+        {
+            let cur = assert_ok!(pool.get());
+            assert_eq!(pool.idle_count(), 0);
+            // Returned cursors come back reset, i.e. unpositioned.
+            assert!(cur.get_raw_key_value().is_err());
+
+            assert_ok!(cur.set_key("b"));
+            assert_ok!(cur.search());
+            let (_, v) = assert_ok!(cur.get_raw_key_value());
+            assert_eq!(assert_ok!(std::str::from_utf8(&v.unwrap())), "2");
+
+            // A second concurrent checkout opens another cursor.
+            let other = assert_ok!(pool.get());
+            assert_ok!(other.next());
+        }
+        assert_eq!(pool.idle_count(), 2);
+    }
+
+    #[test]
+    fn test_cursor_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:first", "key_format=S,value_format=S"));
+        assert_ok!(sess.create("table:second", "key_format=S,value_format=S"));
+        let cache = sess.cursor_cache();
+
+        let cur = assert_ok!(cache.get("table:first"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.set_value("v"));
+        assert_ok!(cur.insert());
+        let ptr = cur.raw_cursor.as_ptr();
+        drop(cur);
+        assert_eq!(cache.idle_count("table:first"), 1);
+
+        // A cursor on another URI doesn't take the idle one.
+        let other = assert_ok!(cache.get("table:second"));
+        assert_ne!(other.raw_cursor.as_ptr(), ptr);
+        drop(other);
+
+        let cur = assert_ok!(cache.get("table:first"));
+        assert_eq!(cur.raw_cursor.as_ptr(), ptr);
+        assert!(cur.get_raw_key_value().is_err());
+        assert_eq!(cache.idle_count("table:first"), 0);
+        assert_eq!(cache.idle_count("table:second"), 1);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::format::WtValue;
+use crate::raw_api::{Error, ErrorKind, Result};
+use crate::Session;
+
+/// How many values `approx_quantiles` samples.
+const SAMPLES: usize = 1024;
+
+impl Session {
+    /// Estimates the `quantiles` (each between 0 and 1, e.g. 0.5 for the
+    /// median) of `uri`'s value, whose first column must be an integer, from a
+    /// random sample of 1024 rows. Rows are sampled with replacement, so the
+    /// estimate's rank is typically within `sqrt(q * (1 - q) / 1024)` of the
+    /// true one, about 1.6% at the median, however big the table; WiredTiger's
+    /// sampling isn't perfectly uniform, so allow more if rows vary a lot in
+    /// size. Fails if the table is empty.
+    pub fn approx_quantiles(&self, uri: &str, quantiles: &[f64]) -> Result<Vec<i64>> {
+        if let Some(q) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+            return Err(Error::new(format!(
+                "quantile must be between 0 and 1, got {}",
+                q
+            )));
+        }
+        // Splitting the table into as many pieces as there are samples spreads
+        // them out more evenly than random descents of the tree.
+        let cursor = self.open_cursor_with_config(
+            uri,
+            &format!("next_random=true,next_random_sample_size={}", SAMPLES),
+        )?;
+        let mut samples = Vec::with_capacity(SAMPLES);
+        for _ in 0..SAMPLES {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => {
+                    return Err(Error::new(format!("{} is empty", uri)));
+                }
+                Err(err) => return Err(err),
+            }
+            samples.push(match cursor.get_value()?.into_iter().next() {
+                Some(WtValue::Int(value)) => value,
+                Some(WtValue::UInt(value)) => i64::try_from(value)
+                    .map_err(|_| Error::new(format!("{} doesn't fit in an i64", value)))?,
+                other => {
+                    return Err(Error::with_kind(
+                        ErrorKind::FormatMismatch,
+                        format!("format mismatch: expected an integer but found {:?}", other),
+                    ))
+                }
+            });
+        }
+        samples.sort_unstable();
+        let last = (samples.len() - 1) as f64;
+        Ok(quantiles
+            .iter()
+            .map(|q| samples[(q * last).round() as usize])
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::{pack, WtValue};
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_approx_quantiles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:uniform", "key_format=Q,value_format=q"));
+        assert!(sess.approx_quantiles("table:uniform", &[0.5]).is_err());
+
+        // The values are spread evenly over 0..10000.
+        let rows: Vec<_> = (0..10_000u64)
+            .map(|i| {
+                let key = assert_ok!(pack("Q", &[WtValue::UInt(i)]));
+                let value = assert_ok!(pack("q", &[WtValue::Int(i as i64)]));
+                (key, value)
+            })
+            .collect();
+        let pairs: Vec<(&[u8], &[u8])> = rows.iter().map(|(k, v)| (&k[..], &v[..])).collect();
+        let cur = assert_ok!(sess.open_cursor_with_config("table:uniform", "raw"));
+        assert_ok!(sess.with_transaction("", |_| cur.batch_insert(&pairs)));
+
+        let estimates = assert_ok!(sess.approx_quantiles("table:uniform", &[0.1, 0.5, 0.9]));
+        assert!(estimates[0] <= estimates[1] && estimates[1] <= estimates[2]);
+        assert!((estimates[1] - 5000).abs() < 500, "median {}", estimates[1]);
+        assert!(sess.approx_quantiles("table:uniform", &[1.5]).is_err());
+    }
+}
@@ -1,6 +1,10 @@
+use crate::format::{self, WtValue};
+use crate::Timestamp;
 use libc::{self, c_char, c_void};
+use std::cell::Cell;
 use std::ffi::{CStr, CString};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use wiredtiger_sys as wtffi;
 
 macro_rules! unwrap_or_panic {
@@ -28,10 +32,7 @@ macro_rules! make_result {
         if $err_code == 0 {
             Ok($ok)
         } else {
-            Err(Error {
-                code: $err_code,
-                message: error_message($err_code),
-            })
+            Err(Error::from_code($err_code))
         }
     };
 }
@@ -48,22 +49,85 @@ pub fn error_message(result: i32) -> String {
     }
 }
 
+/// Returns the (major, minor, patch) version of the linked WiredTiger library.
+pub fn version() -> (i32, i32, i32) {
+    let (mut major, mut minor, mut patch) = (0, 0, 0);
+    unsafe { wtffi::wiredtiger_version(&mut major, &mut minor, &mut patch) };
+    (major, minor, patch)
+}
+
+// The bytes of `item`, for as long as WiredTiger keeps them.
+unsafe fn item_slice<'i>(item: &wtffi::WT_ITEM) -> &'i [u8] {
+    if item.data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(item.data as *const u8, item.size)
+    }
+}
+
+fn empty_item() -> wtffi::WT_ITEM {
+    wtffi::WT_ITEM {
+        data: std::ptr::null(),
+        size: 0,
+        mem: std::ptr::null::<c_void>() as *mut c_void,
+        memsize: 0,
+        flags: 0,
+    }
+}
+
+/// Converts `s` for passing to WiredTiger, failing rather than panicking if it
+/// has an embedded NUL byte.
+pub(crate) fn c_string(s: &str) -> Result<CString> {
+    CString::new(s).map_err(|_| Error::new(format!("{:?} contains a NUL byte", s)))
+}
+
+// Each handle records when it's been closed, so closing it again is a no-op
+// rather than a use after free.
 pub struct RawConnection {
     conn: *mut wtffi::WT_CONNECTION,
+    // Atomic since the connection is shared between threads.
+    closed: AtomicBool,
 }
 
+// WT_CONNECTION methods are thread-safe: WiredTiger expects one connection to be
+// shared by the whole process, with a session per thread.
+unsafe impl Send for RawConnection {}
+unsafe impl Sync for RawConnection {}
+
 pub struct RawSession {
     session: *mut wtffi::WT_SESSION,
+    closed: Cell<bool>,
 }
 
 pub struct RawCursor {
     cursor: *mut wtffi::WT_CURSOR,
+    closed: Cell<bool>,
+    key_format: String,
+    value_format: String,
+    raw: bool,
+    // WiredTiger doesn't copy items passed to set_key/set_value, it keeps a pointer
+    // to them until the next operation, so the buffers have to live here.
+    key_buf: Cell<Vec<u8>>,
+    value_buf: Cell<Vec<u8>>,
+}
+
+/// Broad classification of an `Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An error code returned by WiredTiger, see `Error::code`.
+    WiredTiger,
+    /// A typed accessor was used on a cursor whose key or value format doesn't
+    /// match it, e.g. a string key on a record number (`r`) table.
+    FormatMismatch,
+    /// Any other error raised by this crate rather than by WiredTiger.
+    Other,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
     pub code: i32,
     pub message: String,
+    pub kind: ErrorKind,
 }
 
 impl Error {
@@ -71,330 +135,52 @@ impl Error {
         Self {
             code,
             message: error_message(code),
+            kind: ErrorKind::WiredTiger,
         }
     }
 
     pub fn new<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Other, message)
+    }
+
+    pub fn with_kind<S: Into<String>>(kind: ErrorKind, message: S) -> Self {
         Self {
             code: 0,
             message: message.into(),
+            kind,
         }
     }
-}
-
-struct Modify<'a> {
-    data: &'a [u8],
-    offset: usize,
-}
-
-struct OpenConfig {
-    // in-memory alignment (in bytes) for buffers used for I/O.
-    // The default value of -1 indicates a platform-specific alignment value should be used
-    // (4KB on Linux systems, zero elsewhere). An integer between -1 and 1MB; default -1.
-    buffer_alignment: i32,
-
-    // Assume the heap allocator overhead is the specified percentage,
-    // and adjust the cache usage by that amount (for example, if there is 10GB of data in cache,
-    // a percentage of 10 means WiredTiger treats this as 11GB).
-    // This value is configurable because different heap allocators have different overhead and
-    // different workloads will have different heap allocation sizes and patterns,
-    // therefore applications may need to adjust this value based on allocator choice and behavior in measured workloads.
-    // An integer between 0 and 30; default 8.
-    cache_overhead: u8,
-
-    // Maximum heap memory to allocate for the cache.
-    // A database should configure either cache_size or shared_cache but not both.
-    // An integer between 1MB and 10TB; default 100MB.
-    cache_size: u32,
-
-    checkpoint: CheckpointConfig,
-
-    // Flush files to stable storage when closing or writing checkpoints. Default true.
-    checkpoint_sync: bool,
-
-    // Write the base configuration file if creating the database, see WiredTiger.basecfg file for more information.
-    // Default true.
-    config_base: bool,
-
-    // Create the database if it does not exist. Default false.
-    create: bool,
-
-    // Use O_DIRECT to access files. Options are given as a list, such as "direct_io=[data]".
-    // Configuring direct_io requires care, see Direct I/O for important warnings.
-    // Including "data" will cause WiredTiger data files to use O_DIRECT,
-    // including "log" will cause WiredTiger log files to use O_DIRECT,
-    // and including "checkpoint" will cause WiredTiger data files opened at a checkpoint (i.e: read only) to use O_DIRECT.
-    // list, with values chosen from the following options: "checkpoint", "data", "log"; default empty.
-    direct_io: Vec<DirectIOSetting>,
-
-    // Prefix string for error messages. Default empty.
-    error_prefix: String,
-
-    eviction: EvictionConfig,
-
-    // Continue evicting until the cache has less dirty memory than the value, as a percentage of the total cache size.
-    // Dirty pages will only be evicted if the cache is full enough to trigger eviction. An integer between 10 and 99; default 80.
-    eviction_dirty_target: i8,
-
-    // Continue evicting until the cache has less total memory than the value, as a percentage of the total cache size.
-    // Must be less than eviction_trigger. An integer between 10 and 99; default 80.
-    eviction_target: i8,
-
-    // Trigger eviction when the cache is using this much memory,
-    // as a percentage of the total cache size.
-    // An integer between 10 and 99; default 95.
-    eviction_trigger: i8,
-
-    // Fail if the database already exists, generally used with the create option. Default false.
-    exclusive: bool,
-
-    // list of shared library extensions to load (using dlopen).
-    // Any values specified to an library extension are passed to
-    // WT_CONNECTION::load_extension as the config parameter (for example, extensions=(/path/ext.so={entry=my_entry})).
-    // A list of strings; default empty.
-    extensions: Vec<String>,
-
-    // File extension configuration. If set, extend files of the set type
-    // in allocations of the set size, instead of a block at a time as each
-    // new block is written. For example, file_extend=(data=16MB).
-    // A list, with values chosen from the following options: "data", "log"; default empty.
-    file_extend: Vec<FileExtensionConfigOption>,
-
-    // Maximum number of simultaneous hazard pointers per session handle.
-    // An integer greater than or equal to 15; default 1000.
-    hazard_max: i16,
-
-    log: LogConfig,
-
-    shared_cache: SharedCacheConfig,
-
-    // Maintain database statistics, which may impact performance.
-    // Choosing "all" maintains all statistics regardless of cost,
-    // "fast" maintains a subset of statistics that are relatively inexpensive,
-    // "none" turns off all statistics.
-    // The "clear" configuration resets statistics after they are gathered,
-    // where appropriate (for example, a cache size statistic is not cleared,
-    // while the count of cursor insert operations will be cleared).
-    // When "clear" is configured for the database, gathered statistics are reset
-    // each time a statistics cursor is used to gather statistics, as well as each time
-    // statistics are logged using the statistics_log configuration.
-    //  See Statistics for more information.
-    // A list, with values chosen from the following options: "all", "fast", "none", "clear"; default none.
-    statistics: Vec<StatisticsOption>,
-
-    statistics_log: StatisticsLogConfig,
-
-    transaction_sync: TransactionSyncConfig,
-
-    // Use the WIREDTIGER_CONFIG and WIREDTIGER_HOME environment variables
-    // regardless of whether or not the process is running with special privileges.
-    // See Database Home Directory for more information. A boolean flag; default false.
-    use_environment_priv: bool,
-
-    // Enable messages for various events.
-    // Only available if WiredTiger is configured with –enable-verbose.
-    // Options are given as a list, such as "verbose=[evictserver,read]".
-    // A list, with values chosen from the following options:
-    // "api", "block", "checkpoint", "compact", "evict", "evictserver",
-    // "fileops", "log", "lsm", "metadata", "mutex", "overflow", "read",
-    // "reconcile", "recovery", "salvage", "shared_cache", "split",
-    // "temporary", "transaction", "verify", "version", "write".
-    // Default empty.
-    verbose: Vec<VerboseOption>,
-}
-
-enum VerboseOption {
-    Api,
-    Block,
-    Checkpoint,
-    Compact,
-    Evict,
-    EvictServer,
-    FileOps,
-    Log,
-    Lsm,
-    Metadata,
-    Mutex,
-    Overflow,
-    Read,
-    Reconcile,
-    Recovery,
-    Salvage,
-    SharedCache,
-    Split,
-    Temporary,
-    Transaction,
-    Verify,
-    Version,
-    Write,
-}
-
-// How to sync log records when the transaction commits.
-struct TransactionSyncConfig {
-    //  Whether to sync the log on every commit by default,
-    // can be overridden by the sync setting to WT_SESSION::begin_transaction.
-    // A boolean flag; default false.
-    enabled: bool,
-
-    // The method used to ensure log records are stable on disk,
-    // see Commit-level durability for more information.
-    // A string, chosen from the following options: "dsync", "fsync", "none"; default fsync.
-    method: SyncMethodOption,
-}
-
-enum SyncMethodOption {
-    DSync,
-    FSync,
-    None,
-}
-
-struct StatisticsLogConfig {
-    // log statistics on database close.	a boolean flag; default false.
-    on_close: bool,
-
-    // The pathname to a file into which the log records are written,
-    // may contain ISO C standard strftime conversion specifications.
-    // If the value is not an absolute path name, the file is created
-    // relative to the database home. A string; default "WiredTigerStat.%d.%H".
-    path: String,
-
-    // If non-empty, include statistics for the list of data source URIs,
-    // if they are open at the time of the statistics logging.
-    // The list may include URIs matching a single data source ("table:mytable"),
-    // or a URI matching all data sources of a particular type ("table:").
-    // A list of strings; default empty.
-    sources: Vec<String>,
-
-    // a timestamp prepended to each log record, may contain strftime conversion specifications.	a string; default "%b %d %H:%M:%S".
-    timestamp: String,
-
-    // seconds to wait between each write of the log records; setting this value above 0 configures statistics logging.	an integer between 0 and 100000; default 0.
-    wait: u16,
-}
-
-enum StatisticsOption {
-    All,
-    Fast,
-    None,
-    Clear,
-}
-
-struct LogConfig {
-    // Automatically archive unneeded log files. Default true.
-    archive: bool,
-
-    // Configure a compressor for log records.
-    // Permitted values are "none" or "bzip2", "snappy" or custom compression engine "name"
-    // created with WT_CONNECTION::add_compressor. See Compressors for more information.
-    // a string; default none.
-    compressor: String, // TODO enum?
-
-    // Enable logging subsystem. Default false.
-    enabled: bool,
-
-    // The maximum size of log files. An integer between 100KB and 2GB; default 100MB.
-    file_max: i32,
-
-    // The path to a directory into which the log files are written.
-    // If the value is not an absolute path name, the files are created relative to the database home.
-    // Default empty.
-    path: String,
-
-    // pre-allocate log files.	a boolean flag; default true.
-    prealloc: bool,
-
-    // Run recovery or error if recovery needs to run after an unclean shutdown.
-    // A string, chosen from the following options: "error", "on"; default on.
-    recover: String, // todo enum?
-
-    // Use memory mapping to access files when possible. Default true.
-    mmap: bool,
 
-    // Permit sharing between processes (will automatically start an RPC server
-    // for primary processes and use RPC for secondary processes).
-    // Not yet supported in WiredTiger. A boolean flag; default false.
-    multiprocess: bool,
-
-    // Maximum expected number of sessions (including server threads).
-    // An integer greater than or equal to 1; default 100.
-    session_max: u16,
-}
-
-struct SharedCacheConfig {
-    // The granularity that a shared cache is redistributed.
-    // An integer between 1MB and 10TB; default 10MB.
-    chunk: u32,
-
-    // The name of a cache that is shared between databases or "none" when no shared cache is configured.
-    // Default none.
-    name: String,
-
-    // Amount of cache this database is guaranteed to have available from the shared cache.
-    // This setting is per database. Defaults to the chunk size. Default 0.
-    reserve: u32,
-
-    // Maximum memory to allocate for the shared cache.
-    // Setting this will update the value if one is already set.
-    // An integer between 1MB and 10TB; default 500MB.
-    size: u32,
-}
-
-enum FileExtensionConfigOption {
-    Data,
-    Log,
-}
-
-struct EvictionConfig {
-    // maximum number of threads WiredTiger will start to help evict pages from cache.
-    // The number of threads started will vary depending on the current eviction load.
-    // An integer between 1 and 20; default 1.
-    threads_max: u8,
-    // minimum number of threads WiredTiger will start to help evict pages from cache.
-    // The number of threads currently running will vary depending on the current eviction load.
-    // An integer between 1 and 20; default 1.
-    threads_min: u8,
-}
-
-enum DirectIOSetting {
-    Checkpoint,
-    Data,
-    Log,
-}
-
-struct CheckpointConfig {
-    // Wait for this amount of log record bytes to be written to the log between each checkpoint.
-    // A database can configure both log_size and wait to set an upper bound for checkpoints;
-    // Setting this value above 0 configures periodic checkpoints.	An integer between 0 and 2GB; default 0.
-    log_size: i32,
-
-    // The checkpoint name. Default "WiredTigerCheckpoint".
-    name: String,
+    /// Returns true if this error is WiredTiger's `WT_NOTFOUND`, e.g. a cursor
+    /// that has moved past the last record.
+    pub fn is_not_found(&self) -> bool {
+        self.code == wtffi::WT_NOTFOUND
+    }
 
-    // Seconds to wait between each checkpoint; setting this value above 0 configures periodic checkpoints.
-    // An integer between 0 and 100000; default 0.
-    wait: i16,
+    /// Returns true if this error is WiredTiger's `WT_DUPLICATE_KEY`, e.g. an
+    /// insert of an existing key through a cursor opened with `overwrite=false`.
+    pub fn is_duplicate_key(&self) -> bool {
+        self.code == wtffi::WT_DUPLICATE_KEY
+    }
 }
 
-struct AsyncConfig {
-    // Enable asynchronous operation.	a boolean flag; default false.
-    enabled: bool,
-
-    // Maximum number of expected simultaneous asynchronous operations.
-    // An integer between 10 and 4096; default 1024.
-    ops_max: u16,
-
-    // The number of worker threads to service asynchronous requests.
-    // An integer between 1 and 20; default 2.
-    threads: u8,
+/// The last error a session reported, from `Session::last_error`. Along with the
+/// error code, WiredTiger gives a sub-level code saying more about the cause,
+/// e.g. `WT_WRITE_CONFLICT` for a `WT_ROLLBACK`, or `WT_NONE` if it has nothing
+/// to add.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastError {
+    pub err: i32,
+    pub sub_level: i32,
+    pub message: String,
 }
 
-struct LSMManagerConfig {
-    // Merge LSM chunks where possible. Default true.
-    merge: bool,
-
-    // Configure a set of threads to manage merging LSM trees in the database.
-    // An integer between 3 and 20; default 4.
-    worker_thread_max: u8,
+/// One change made by `RawCursor::modify`: the `size` bytes at `offset` in the
+/// value are replaced by `data`.
+pub(crate) struct Modify<'a> {
+    pub data: &'a [u8],
+    pub offset: usize,
+    pub size: usize,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -402,39 +188,119 @@ pub type Result<T> = std::result::Result<T, Error>;
 impl RawConnection {
     /// Opens a wiredtiger file at the given path by calling `wiredtiger_open()`.
     pub fn open(filename: &str, options: &str) -> Result<Self> {
+        Self::open_with_event_handler(filename, options, ptr::null_mut())
+    }
+
+    /// Like `open`, but installs `event_handler` to receive WiredTiger's error,
+    /// message and progress callbacks. The handler must outlive the connection.
+    pub fn open_with_event_handler(
+        filename: &str,
+        options: &str,
+        event_handler: *mut wtffi::WT_EVENT_HANDLER,
+    ) -> Result<Self> {
         // outparam destination for wiredtiger_open()
         let mut conn: *mut wtffi::WT_CONNECTION = ptr::null_mut();
 
-        let options = CString::new(options).unwrap();
-        let dbpath = CString::new(filename).unwrap();
-
-        // TODO: support a non-null event handler.
-        let event_handler: *const wtffi::WT_EVENT_HANDLER = ptr::null();
+        let options = c_string(options)?;
+        let dbpath = c_string(filename)?;
 
         let err_code = unsafe {
-            wtffi::wiredtiger_open(
-                dbpath.as_ptr(),
-                event_handler as *mut wtffi::WT_EVENT_HANDLER,
-                options.as_ptr(),
-                &mut conn,
-            )
+            wtffi::wiredtiger_open(dbpath.as_ptr(), event_handler, options.as_ptr(), &mut conn)
         };
-        make_result!(err_code, RawConnection { conn })
+        make_result!(
+            err_code,
+            RawConnection {
+                conn,
+                closed: AtomicBool::new(false),
+            }
+        )
+        .map_err(|err| {
+            // Without `create`, an empty or missing home directory is reported as
+            // corruption. Only a directory with a WiredTiger file is really corrupt.
+            if err.code == wtffi::WT_TRY_SALVAGE
+                && !std::path::Path::new(filename).join("WiredTiger").exists()
+            {
+                Error {
+                    message: format!(
+                        "no WiredTiger database found at {}; pass create=true to initialize",
+                        filename
+                    ),
+                    ..err
+                }
+            } else {
+                err
+            }
+        })
     }
 
     // TODO
-    // pub fn add_collator(&self, const char * name, WT_COLLATOR * collator, const char * config )
-    // pub fn add_compressor(&self, const char * name, WT_COMPRESSOR * compressor, const char * config )
     // pub fn add_data_source(&self, const char * prefix, WT_DATA_SOURCE * data_source, const char * config )
     // pub fn add_encryptor(&self, const char * name, WT_ENCRYPTOR * encryptor, const char * config )
 
+    /// Registers `collator` under `name`. WiredTiger keeps the pointer until the
+    /// connection is closed, at which point it calls the collator's `terminate`.
+    pub fn add_collator(&self, name: &str, collator: *mut wtffi::WT_COLLATOR) -> Result<()> {
+        let name = c_string(name)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.conn).add_collator,
+                self.conn,
+                name.as_ptr(),
+                collator,
+                ptr::null()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    /// Registers `compressor` under `name`, for use as a `block_compressor`. As with
+    /// collators, WiredTiger calls `terminate` on it when the connection closes.
+    pub fn add_compressor(&self, name: &str, compressor: *mut wtffi::WT_COMPRESSOR) -> Result<()> {
+        let name = c_string(name)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.conn).add_compressor,
+                self.conn,
+                name.as_ptr(),
+                compressor,
+                ptr::null()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    /// Registers `extractor` under `name`, for indices created with
+    /// `extractor=<name>`. WiredTiger calls `terminate` on it when the connection
+    /// closes.
+    pub fn add_extractor(&self, name: &str, extractor: *mut wtffi::WT_EXTRACTOR) -> Result<()> {
+        let name = c_string(name)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.conn).add_extractor,
+                self.conn,
+                name.as_ptr(),
+                extractor,
+                ptr::null()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    /// Closes the connection, along with its sessions and cursors. Closing it
+    /// again does nothing.
     pub fn close(&self) -> Result<()> {
+        if self.closed.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
         let err_code = unsafe { unwrap_or_panic!((*self.conn).close, self.conn, std::ptr::null()) };
         make_result!(err_code, ())
     }
 
     pub fn close_with_config(&self, config: &str) -> Result<()> {
-        let config = CString::new(config).unwrap();
+        let config = c_string(config)?;
+        if self.closed.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
         let err_code = unsafe { unwrap_or_panic!((*self.conn).close, self.conn, config.as_ptr()) };
         make_result!(err_code, ())
     }
@@ -458,10 +324,9 @@ impl RawConnection {
                 ))),
             }
         } else {
-            Err(Error {
-                code: 0,
-                message: "received null from calling get_home on WT_CONNECTION".to_string(),
-            })
+            Err(Error::new(
+                "received null from calling get_home on WT_CONNECTION",
+            ))
         }
     }
 
@@ -470,56 +335,167 @@ impl RawConnection {
         new_val != 0
     }
 
-    // TODO
-    // pun fn load_extension(&self, const char * path, const char * config )
+    pub fn load_extension(&self, path: &str, config: &str) -> Result<()> {
+        let path = c_string(path)?;
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.conn).load_extension,
+                self.conn,
+                path.as_ptr(),
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
 
     pub fn open_session(&self) -> Result<RawSession> {
+        self.open_session_with("")
+    }
+
+    /// Like `open_session`, with `config` (e.g. `isolation=snapshot`) passed to
+    /// WiredTiger.
+    pub fn open_session_with(&self, config: &str) -> Result<RawSession> {
+        self.open_session_with_event_handler(config, ptr::null_mut())
+    }
+
+    /// Like `open_session_with`, but installs `event_handler` to receive the
+    /// session's error, message and progress callbacks in place of the
+    /// connection's. The handler must outlive the session.
+    pub fn open_session_with_event_handler(
+        &self,
+        config: &str,
+        event_handler: *mut wtffi::WT_EVENT_HANDLER,
+    ) -> Result<RawSession> {
+        let config = c_string(config)?;
         let mut session: *mut wtffi::WT_SESSION = ptr::null_mut();
-        let event_handler: *mut wtffi::WT_EVENT_HANDLER = ptr::null_mut();
         let err_code = unsafe {
             unwrap_or_panic!(
                 (*self.conn).open_session,
                 self.conn,
                 event_handler,
-                ptr::null(),
+                config.as_ptr(),
                 &mut session
             )
         };
-        make_result!(err_code, RawSession { session })
+        make_result!(
+            err_code,
+            RawSession {
+                session,
+                closed: Cell::new(false),
+            }
+        )
     }
 
-    // TODO
-    // pun fn query_timestamp(&self, char * hex_timestamp, const char * config )
+    /// Queries one of the connection's global timestamps (e.g. `get=stable_timestamp`).
+    pub fn query_timestamp(&self, config: &str) -> Result<Timestamp> {
+        let config = c_string(config)?;
+        // Timestamps are 64 bits, i.e. at most 16 hex digits plus a nul.
+        let mut hex: [c_char; 17] = [0; 17];
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.conn).query_timestamp,
+                self.conn,
+                hex.as_mut_ptr(),
+                config.as_ptr()
+            )
+        };
+        if err_code != 0 {
+            return Err(Error::from_code(err_code));
+        }
+        Timestamp::from_hex(&unsafe { CStr::from_ptr(hex.as_ptr()) }.to_string_lossy())
+    }
 
     pub fn reconfigure(&self, config: &str) -> Result<()> {
-        let config = CString::new(config).unwrap();
+        let config = c_string(config)?;
         let err_code =
             unsafe { unwrap_or_panic!((*self.conn).reconfigure, self.conn, config.as_ptr()) };
         make_result!(err_code, ())
     }
 
-    // pun fn rollback_to_stable(&self, const char * config )
+    /// Rolls every table back to the stable timestamp, discarding updates made
+    /// after it. There mustn't be any open cursors or running transactions.
+    pub fn rollback_to_stable(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!((*self.conn).rollback_to_stable, self.conn, config.as_ptr())
+        };
+        make_result!(err_code, ())
+    }
+
     // pun fn set_file_system(&self, WT_FILE_SYSTEM * fs, const char * config )
-    // pun fn set_timestamp(&self, const char * config )
+
+    /// Sets the connection's global timestamps, e.g. `oldest_timestamp=` or
+    /// `stable_timestamp=`.
+    pub fn set_timestamp(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code =
+            unsafe { unwrap_or_panic!((*self.conn).set_timestamp, self.conn, config.as_ptr()) };
+        make_result!(err_code, ())
+    }
 }
 
 impl RawSession {
-    // pub fn alter(&self, const char * name, const char * config )
-    // pub fn begin_transaction(&self, const char * config )
+    pub fn alter(&self, name: &str, config: &str) -> Result<()> {
+        let name = c_string(name)?;
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).alter,
+                self.session,
+                name.as_ptr(),
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    pub fn begin_transaction(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).begin_transaction,
+                self.session,
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
     // pub fn bind_configuration(&self, const char * compiled, ... )
-    // pub fn checkpoint(&self, const char * config )
 
+    pub fn checkpoint(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code =
+            unsafe { unwrap_or_panic!((*self.session).checkpoint, self.session, config.as_ptr()) };
+        make_result!(err_code, ())
+    }
+
+    /// Closes the session and its cursors. Closing it again does nothing.
     pub fn close(&self) -> Result<()> {
+        if self.closed.replace(true) {
+            return Ok(());
+        }
         let err_code =
             unsafe { unwrap_or_panic!((*self.session).close, self.session, std::ptr::null()) };
         make_result!(err_code, ())
     }
 
-    // pub fn commit_transaction(&self, const char * config )
+    pub fn commit_transaction(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).commit_transaction,
+                self.session,
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
 
     pub fn compact(&self, name: &str, config: &str) -> Result<()> {
-        let name = CString::new(name).unwrap();
-        let config = CString::new(config).unwrap();
+        let name = c_string(name)?;
+        let config = c_string(config)?;
         let err_code = unsafe {
             unwrap_or_panic!(
                 (*self.session).compact,
@@ -532,8 +508,8 @@ impl RawSession {
     }
 
     pub fn create(&self, name: &str, config: &str) -> Result<()> {
-        let name = CString::new(name).unwrap();
-        let config = CString::new(config).unwrap();
+        let name = c_string(name)?;
+        let config = c_string(config)?;
         make_result!(
             unsafe {
                 unwrap_or_panic!(
@@ -547,8 +523,8 @@ impl RawSession {
         )
     }
     pub fn drop(&self, name: &str, config: &str) -> Result<()> {
-        let name = CString::new(name).unwrap();
-        let config = CString::new(config).unwrap();
+        let name = c_string(name)?;
+        let config = c_string(config)?;
         let err_code = unsafe {
             unwrap_or_panic!(
                 (*self.session).drop,
@@ -559,11 +535,90 @@ impl RawSession {
         };
         make_result!(err_code, ())
     }
-    // pub fn get_last_error(&self, int * err, int * sub_level_err, const char ** err_msg )
-    // pub fn log_flush(&self, const char * config )
-    // pub fn log_printf(&self, const char * format, ... )
+
+    /// Copies the objects of tiered tables checkpointed since the last flush to
+    /// shared storage.
+    pub fn flush_tier(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code =
+            unsafe { unwrap_or_panic!((*self.session).flush_tier, self.session, config.as_ptr()) };
+        make_result!(err_code, ())
+    }
+    /// Details of the last error returned by an operation on this session.
+    pub fn get_last_error(&self) -> LastError {
+        let mut err = 0;
+        let mut sub_level = 0;
+        let mut message: *const c_char = ptr::null();
+        unsafe {
+            unwrap_or_panic!(
+                (*self.session).get_last_error,
+                self.session,
+                &mut err,
+                &mut sub_level,
+                &mut message
+            );
+        }
+        let message = if message.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        LastError {
+            err,
+            sub_level,
+            message,
+        }
+    }
+
+    /// Adds `ref_cursor`, an index or table cursor positioned on a key, to the
+    /// join cursor `join_cursor`.
+    pub fn join(
+        &self,
+        join_cursor: &RawCursor,
+        ref_cursor: &RawCursor,
+        config: &str,
+    ) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).join,
+                self.session,
+                join_cursor.cursor,
+                ref_cursor.cursor,
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    /// Flushes the log, e.g. with `sync=on` to wait until it's on disk.
+    pub fn log_flush(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code =
+            unsafe { unwrap_or_panic!((*self.session).log_flush, self.session, config.as_ptr()) };
+        make_result!(err_code, ())
+    }
+
+    /// Writes `msg` to the log as a message record.
+    pub fn log_printf(&self, msg: &str) -> Result<()> {
+        // Passed as an argument to "%s", so a `%` in the message is harmless.
+        let format = CString::new("%s").unwrap();
+        let msg = c_string(msg)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).log_printf,
+                self.session,
+                format.as_ptr(),
+                msg.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
     pub fn open_cursor(&self, uri: &str) -> Result<RawCursor> {
-        let uri = CString::new(uri).unwrap();
+        let uri = c_string(uri)?;
         let mut cursor: *mut wtffi::WT_CURSOR = ptr::null_mut();
         let cursor_null: *const wtffi::WT_CURSOR = ptr::null();
         let result = unsafe {
@@ -576,12 +631,46 @@ impl RawSession {
                 &mut cursor
             )
         };
-        make_result!(result, RawCursor { cursor })
+        make_result!(result, RawCursor::new(cursor))
+    }
+
+    pub fn open_cursor_with_config(&self, uri: &str, config: &str) -> Result<RawCursor> {
+        let uri = c_string(uri)?;
+        let config = c_string(config)?;
+        let mut cursor: *mut wtffi::WT_CURSOR = ptr::null_mut();
+        let result = unsafe {
+            unwrap_or_panic!(
+                (*self.session).open_cursor,
+                self.session,
+                uri.as_ptr(),
+                ptr::null_mut(),
+                config.as_ptr(),
+                &mut cursor
+            )
+        };
+        make_result!(result, RawCursor::new(cursor))
     }
     // pub fn prepare_transaction(&self, const char * config )
-    // pub fn query_timestamp(&self, char * hex_timestamp, const char * config )
+    /// Queries one of the session's transaction timestamps (e.g. `get=read`).
+    pub fn query_timestamp(&self, config: &str) -> Result<Timestamp> {
+        let config = c_string(config)?;
+        // Timestamps are 64 bits, i.e. at most 16 hex digits plus a nul.
+        let mut hex: [c_char; 17] = [0; 17];
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).query_timestamp,
+                self.session,
+                hex.as_mut_ptr(),
+                config.as_ptr()
+            )
+        };
+        if err_code != 0 {
+            return Err(Error::from_code(err_code));
+        }
+        Timestamp::from_hex(&unsafe { CStr::from_ptr(hex.as_ptr()) }.to_string_lossy())
+    }
     pub fn reconfigure(&self, config: &str) -> Result<()> {
-        let config = CString::new(config).unwrap();
+        let config = c_string(config)?;
         let err_code =
             unsafe { unwrap_or_panic!((*self.session).reconfigure, self.session, config.as_ptr()) };
         make_result!(err_code, ())
@@ -595,17 +684,117 @@ impl RawSession {
         let err_code = unsafe { unwrap_or_panic!((*self.session).reset_snapshot, self.session) };
         make_result!(err_code, ())
     }
-    // pub fn rollback_transaction(&self, const char * config )
+    pub fn rollback_transaction(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).rollback_transaction,
+                self.session,
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
+    /// Unpacks `buf` according to `format`, which must be a sequence of unsigned
+    /// integer types (e.g. `QQB`, no repeat counts). Returns the values and the
+    /// number of bytes they took up.
+    pub fn unpack_uints(&self, format: &str, buf: &[u8]) -> Result<(Vec<u64>, usize)> {
+        let c_format = c_string(format)?;
+        let mut stream: *mut wtffi::WT_PACK_STREAM = ptr::null_mut();
+        let err_code = unsafe {
+            wtffi::wiredtiger_unpack_start(
+                self.session,
+                c_format.as_ptr(),
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                &mut stream,
+            )
+        };
+        if err_code != 0 {
+            return Err(Error::from_code(err_code));
+        }
+
+        let mut values = Vec::with_capacity(format.len());
+        let mut err_code = 0;
+        for _ in format.chars() {
+            let mut value = 0;
+            err_code = unsafe { wtffi::wiredtiger_unpack_uint(stream, &mut value) };
+            if err_code != 0 {
+                break;
+            }
+            values.push(value);
+        }
+        // The stream has to be closed even if unpacking failed.
+        let mut used = 0;
+        let close_code = unsafe { wtffi::wiredtiger_pack_close(stream, &mut used) };
+        if err_code != 0 {
+            return Err(Error::from_code(err_code));
+        }
+        make_result!(close_code, (values, used))
+    }
+
     // pub fn salvage(&self, const char * name, const char * config )
     // pub fn set_last_error(&self, int err, int sub_level_err )
     // const char* strerror(&self, int error )
-    // int timestamp_transaction(&self, const char * config )
+    pub fn timestamp_transaction(&self, config: &str) -> Result<()> {
+        let config = c_string(config)?;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).timestamp_transaction,
+                self.session,
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
     // int timestamp_transaction_uint(&self, WT_TS_TXN_TYPE which, uint64_t ts )
-    // int transaction_pinned_range(&self, uint64_t * range )
-    // int truncate(&self, const char * name, WT_CURSOR * start, WT_CURSOR * stop, const char * config )
+    /// How many transaction IDs the session's running transaction keeps pinned,
+    /// i.e. how far behind the newest transaction its snapshot is.
+    pub fn transaction_pinned_range(&self) -> Result<u64> {
+        let mut range: u64 = 0;
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).transaction_pinned_range,
+                self.session,
+                &mut range
+            )
+        };
+        make_result!(err_code, range)
+    }
+
+    /// Removes the records of `name` from `start` to `stop`, both positioned
+    /// cursors on it, or from its beginning or to its end if either is `None`.
+    /// With no cursors `name` is required, and is emptied entirely.
+    pub fn truncate(
+        &self,
+        name: Option<&str>,
+        start: Option<&RawCursor>,
+        stop: Option<&RawCursor>,
+        config: &str,
+    ) -> Result<()> {
+        let name = name.map(c_string).transpose()?;
+        let config = c_string(config)?;
+        let cursor = |c: Option<&RawCursor>| c.map_or(ptr::null_mut(), |c| c.cursor);
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.session).truncate,
+                self.session,
+                name.as_ref().map_or(ptr::null(), |name| name.as_ptr()),
+                cursor(start),
+                cursor(stop),
+                config.as_ptr()
+            )
+        };
+        make_result!(err_code, ())
+    }
+
     // int verify(&self, const char * name, const char * config )
 }
 
+/// How a cursor's key compares with another key, e.g. from `search_near`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompareStatus {
     LessThan,
     Equal,
@@ -613,7 +802,9 @@ pub enum CompareStatus {
 }
 
 impl CompareStatus {
-    fn from_code(code: i32) -> Self {
+    /// Interprets a comparison result as WiredTiger returns it, where only the
+    /// sign matters.
+    pub fn from_code(code: i32) -> Self {
         match code {
             x if x < 0 => Self::LessThan,
             0 => Self::Equal,
@@ -622,7 +813,46 @@ impl CompareStatus {
     }
 }
 
+/// Identifies an open cursor without owning it, so a session can keep track
+/// of its cursors.
+#[derive(PartialEq, Eq)]
+pub(crate) struct CursorHandle(*mut wtffi::WT_CURSOR);
+
+impl CursorHandle {
+    /// Resets the cursor, which must still be open.
+    pub(crate) unsafe fn reset(&self) -> Result<()> {
+        let err_code = unwrap_or_panic!((*self.0).reset, self.0);
+        make_result!(err_code, ())
+    }
+}
+
 impl RawCursor {
+    fn new(cursor: *mut wtffi::WT_CURSOR) -> Self {
+        let format = |ptr: *const c_char| {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                unsafe { from_cstr(ptr) }
+            }
+        };
+        let (key_format, value_format, raw) = unsafe {
+            (
+                format((*cursor).key_format),
+                format((*cursor).value_format),
+                (*cursor).flags & wtffi::WT_CURSTD_RAW as u64 != 0,
+            )
+        };
+        Self {
+            cursor,
+            closed: Cell::new(false),
+            key_format,
+            value_format,
+            raw,
+            key_buf: Cell::new(Vec::new()),
+            value_buf: Cell::new(Vec::new()),
+        }
+    }
+
     // TODO
     // pub fn get_key(&self,	, ... )
     // pub fn get_value(&self,	 ... )
@@ -631,13 +861,17 @@ impl RawCursor {
     // pub fn WT_CURSOR::update	(	WT_CURSOR * 	cursor	)
 
     pub fn bound(&self, config: &str) -> Result<()> {
-        let config = CString::new(config).unwrap();
+        let config = c_string(config)?;
         let err_code =
             unsafe { unwrap_or_panic!((*self.cursor).bound, self.cursor, config.as_ptr()) };
         make_result!(err_code, ())
     }
 
+    /// Closes the cursor. Closing it again does nothing.
     pub fn close(&self) -> Result<()> {
+        if self.closed.replace(true) {
+            return Ok(());
+        }
         let err_code = unsafe { unwrap_or_panic!((*self.cursor).close, self.cursor) };
         make_result!(err_code, ())
     }
@@ -705,6 +939,45 @@ impl RawCursor {
         })
     }
 
+    /// Like `get_raw_key_value`, but returns the packed bytes exactly as WiredTiger
+    /// stores them (including the nul terminator of an `S` column).
+    pub fn get_packed_key_value(&self) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut key = empty_item();
+        let mut value = empty_item();
+
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.cursor).get_raw_key_value,
+                self.cursor,
+                &mut key,
+                &mut value
+            )
+        };
+        make_result!(err_code, unsafe {
+            (
+                raw_data(key.data as *const c_char, key.size).unwrap_or_default(),
+                raw_data(value.data as *const c_char, value.size).unwrap_or_default(),
+            )
+        })
+    }
+
+    /// Like `get_packed_key_value`, but borrows the bytes from WiredTiger rather
+    /// than copying them. They're only valid until the cursor is next moved,
+    /// modified or closed, which the borrow of `self` can't enforce, so callers
+    /// must.
+    pub(crate) unsafe fn packed_key_value_slices(&self) -> Result<(&[u8], &[u8])> {
+        let mut key = empty_item();
+        let mut value = empty_item();
+
+        let err_code = unwrap_or_panic!(
+            (*self.cursor).get_raw_key_value,
+            self.cursor,
+            &mut key,
+            &mut value
+        );
+        make_result!(err_code, (item_slice(&key), item_slice(&value)))
+    }
+
     //pub fn get_key(&self) -> Result<()> {
     //    let err_code = unsafe {
     //        let some_val: u16 = 0;
@@ -717,28 +990,11 @@ impl RawCursor {
     //    make_result!(err_code, ())
     //}
 
-    pub fn get_value(&self) -> Result<()> {
-        /*
-            Format	C Type	Python type	Notes
-            x	N/A	N/A	pad byte, no associated value
-            b	int8_t	int	signed byte
-            B	uint8_t	int	unsigned byte
-            h	int16_t	int	signed 16-bit
-            H	uint16_t	int	unsigned 16-bit
-            i	int32_t	int	signed 32-bit
-            I	uint32_t	int	unsigned 32-bit
-            l	int32_t	int	signed 32-bit
-            L	uint32_t	int	unsigned 32-bit
-            q	int64_t	int	signed 64-bit
-            Q	uint64_t	int	unsigned 64-bit
-            r	uint64_t	int	record number
-            s	char[]	str	fixed-length string
-            S	char[]	str	NUL-terminated string
-            t	uint8_t	int	fixed-length bit field
-            u	WT_ITEM *	bytes	raw byte array
-        */
-
-        todo!();
+    /// Decodes the current value into one `WtValue` per column of the value
+    /// format, e.g. the projected columns of a `table:name(col1,col2)` cursor.
+    pub fn get_value(&self) -> Result<Vec<WtValue>> {
+        let (_, value) = self.get_packed_key_value()?;
+        format::unpack(&self.value_format, &value)
     }
 
     pub fn insert(&self) -> Result<()> {
@@ -751,8 +1007,10 @@ impl RawCursor {
         make_result!(err_code, ())
     }
 
-    pub fn modify<'a, M: Iterator<Item = Modify<'a>>>(&self, ms: M) {
-        let ms: Vec<_> = ms
+    /// Changes the value at the cursor's key in place. It has to be called in a
+    /// transaction.
+    pub(crate) fn modify<'a, M: Iterator<Item = Modify<'a>>>(&self, ms: M) -> Result<()> {
+        let mut ms: Vec<_> = ms
             .map(|m| wtffi::WT_MODIFY {
                 data: wtffi::WT_ITEM {
                     data: m.data.as_ptr() as *const c_void,
@@ -762,11 +1020,19 @@ impl RawCursor {
                     flags: 0,
                 },
                 offset: m.offset,
-                size: todo!(),
+                size: m.size,
             })
             .collect();
 
-        panic!("Asf");
+        let err_code = unsafe {
+            unwrap_or_panic!(
+                (*self.cursor).modify,
+                self.cursor,
+                ms.as_mut_ptr(),
+                ms.len() as i32
+            )
+        };
+        make_result!(err_code, ())
     }
     pub fn next(&self) -> Result<()> {
         let err_code = unsafe { unwrap_or_panic!((*self.cursor).next, self.cursor) };
@@ -778,7 +1044,7 @@ impl RawCursor {
     }
 
     pub fn reconfigure(&self, config: &str) -> Result<()> {
-        let config = CString::new(config).unwrap();
+        let config = c_string(config)?;
         let err_code =
             unsafe { unwrap_or_panic!((*self.cursor).reconfigure, self.cursor, config.as_ptr()) };
         make_result!(err_code, ())
@@ -815,29 +1081,131 @@ impl RawCursor {
         };
         make_result!(err_code, CompareStatus::from_code(comparep))
     }
-    pub fn set_key(&self, key: &str) {
-        let key = CString::new(key).unwrap();
+    #[cfg(test)]
+    pub(crate) fn as_ptr(&self) -> *mut wtffi::WT_CURSOR {
+        self.cursor
+    }
+
+    pub(crate) fn handle(&self) -> CursorHandle {
+        CursorHandle(self.cursor)
+    }
+
+    /// The URI the cursor was opened on.
+    pub fn uri(&self) -> String {
+        unsafe { from_cstr((*self.cursor).uri) }
+    }
+
+    /// The cursor's key format, as reported by WiredTiger when it was opened.
+    pub fn key_format(&self) -> &str {
+        &self.key_format
+    }
+
+    /// The cursor's value format, as reported by WiredTiger when it was opened.
+    pub fn value_format(&self) -> &str {
+        &self.value_format
+    }
+
+    fn expect_format(which: &str, format: &str, supplied: &str) -> Result<()> {
+        if format == supplied {
+            Ok(())
+        } else {
+            Err(Error::with_kind(
+                ErrorKind::FormatMismatch,
+                format!(
+                    "format mismatch: cursor {}_format is \"{}\" but a \"{}\" {} was supplied",
+                    which, format, supplied, which
+                ),
+            ))
+        }
+    }
+
+    // A raw cursor takes every key and value as a packed `WT_ITEM`, so passing it
+    // a string would have WiredTiger misread the pointer.
+    fn expect_not_raw(&self, which: &str) -> Result<()> {
+        if !self.raw {
+            return Ok(());
+        }
+        Err(Error::with_kind(
+            ErrorKind::FormatMismatch,
+            format!(
+                "format mismatch: cursor was opened with raw, so its {} must be a packed item",
+                which
+            ),
+        ))
+    }
+
+    pub fn set_key(&self, key: &str) -> Result<()> {
+        self.expect_not_raw("key")?;
+        Self::expect_format("key", &self.key_format, "S")?;
+        let key = c_string(key)?;
 
         unsafe {
-            unwrap_or_panic!((*self.cursor).set_key, self.cursor, key);
+            unwrap_or_panic!((*self.cursor).set_key, self.cursor, key.as_ptr());
         };
+        self.key_buf.set(key.into_bytes_with_nul());
+        Ok(())
     }
 
-    pub fn set_value(&self, value: &str) {
-        let value = CString::new(value).unwrap();
+    pub fn set_value(&self, value: &str) -> Result<()> {
+        self.expect_not_raw("value")?;
+        Self::expect_format("value", &self.value_format, "S")?;
+        let value = c_string(value)?;
 
         unsafe {
-            unwrap_or_panic!((*self.cursor).set_value, self.cursor, value);
+            unwrap_or_panic!((*self.cursor).set_value, self.cursor, value.as_ptr());
         };
+        self.value_buf.set(value.into_bytes_with_nul());
+        Ok(())
     }
 
-    pub fn set_key_value(&self, key: &str, value: &str) {
-        self.set_key(key);
-        self.set_value(value);
+    /// Sets the key from a byte buffer passed as a `WT_ITEM`. This is what WiredTiger
+    /// expects for `u` formats, and for every format on a cursor opened with `raw`.
+    pub fn set_key_item(&self, key: &[u8]) -> Result<()> {
+        if !self.raw {
+            Self::expect_format("key", &self.key_format, "u")?;
+        }
+        let buf = key.to_vec();
+        let mut item = empty_item();
+        item.data = buf.as_ptr() as *const c_void;
+        item.size = buf.len();
+        unsafe {
+            unwrap_or_panic!(
+                (*self.cursor).set_key,
+                self.cursor,
+                &mut item as *mut wtffi::WT_ITEM
+            );
+        };
+        self.key_buf.set(buf);
+        Ok(())
+    }
+
+    /// Sets the value from a byte buffer passed as a `WT_ITEM`, see `set_key_item`.
+    pub fn set_value_item(&self, value: &[u8]) -> Result<()> {
+        if !self.raw {
+            Self::expect_format("value", &self.value_format, "u")?;
+        }
+        let buf = value.to_vec();
+        let mut item = empty_item();
+        item.data = buf.as_ptr() as *const c_void;
+        item.size = buf.len();
+        unsafe {
+            unwrap_or_panic!(
+                (*self.cursor).set_value,
+                self.cursor,
+                &mut item as *mut wtffi::WT_ITEM
+            );
+        };
+        self.value_buf.set(buf);
+        Ok(())
+    }
+
+    pub fn set_key_value(&self, key: &str, value: &str) -> Result<()> {
+        self.set_key(key)?;
+        self.set_value(value)
     }
 
     pub fn update(&self) -> Result<()> {
-        let err_code = unsafe { unwrap_or_panic!((*self.cursor).insert, self.cursor) };
+        let err_code = unsafe { unwrap_or_panic!((*self.cursor).update, self.cursor) };
         make_result!(err_code, ())
     }
 }
@@ -847,6 +1215,33 @@ mod tests {
     use super::*;
     use assert_ok::assert_ok;
 
+    #[test]
+    fn test_open_with_typed_config() {
+        // config.rs holds the only copy of the connection config types.
+        let config = crate::OpenConnectionConfig::builder()
+            .create(true)
+            .log_enabled(true)
+            .build();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(RawConnection::open(
+            temp_dir.path().to_str().unwrap(),
+            &config.to_string()
+        ));
+        assert_ok!(conn.open_session());
+        assert_ok!(conn.close());
+    }
+
+    #[test]
+    fn test_compare_status_from_code() {
+        assert_eq!(CompareStatus::from_code(-5), CompareStatus::LessThan);
+        assert_eq!(CompareStatus::from_code(0), CompareStatus::Equal);
+        assert_eq!(CompareStatus::from_code(7), CompareStatus::GreaterThan);
+        assert_eq!(
+            format!("{:?}", CompareStatus::from_code(i32::MIN)),
+            "LessThan"
+        );
+    }
+
     #[test]
     fn test() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -859,18 +1254,18 @@ mod tests {
 
         // insert a k/v
         let cursor = assert_ok!(session.open_cursor("table:mytable"));
-        cursor.set_key("tyler");
-        cursor.set_value("brock");
+        assert_ok!(cursor.set_key("tyler"));
+        assert_ok!(cursor.set_value("brock"));
         assert_ok!(cursor.insert());
 
         // insert another k/v
-        cursor.set_key("mike");
-        cursor.set_value("obrien");
+        assert_ok!(cursor.set_key("mike"));
+        assert_ok!(cursor.set_value("obrien"));
         assert_ok!(cursor.insert());
         assert_ok!(cursor.reset());
 
         // search for the first inserted one again
-        cursor.set_key("tyler");
+        assert_ok!(cursor.set_key("tyler"));
         assert_ok!(cursor.search());
         let (k, v) = assert_ok!(cursor.get_raw_key_value());
         let (k, v) = (k.unwrap(), v.unwrap());
@@ -882,4 +1277,25 @@ mod tests {
         assert_ok!(session.close());
         assert_ok!(conn.close());
     }
+
+    #[test]
+    fn test_close_twice() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(RawConnection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let session = assert_ok!(conn.open_session());
+        assert_ok!(session.create("table:twice", "key_format=S,value_format=S"));
+        let cursor = assert_ok!(session.open_cursor("table:twice"));
+
+        // The second close of each handle doesn't reach WiredTiger, which would
+        // be a use after free.
+        for _ in 0..2 {
+            assert_ok!(cursor.close());
+            assert_ok!(session.close());
+        }
+        assert_ok!(conn.close());
+        assert_ok!(conn.close_with_config("leak_memory=true"));
+    }
 }
@@ -0,0 +1,208 @@
+use crate::raw_api::{Error, ErrorKind, Result};
+use crate::{CompareStatus, Cursor};
+
+/// The keys of a ranged scan, from `Cursor::scan_keys_between`. Dropping it
+/// resets the cursor, lifting the bounds, even if it wasn't run to the end.
+pub struct KeyScan<'c, 'a> {
+    cursor: &'c Cursor<'a>,
+    bounded: Option<Result<()>>,
+    done: bool,
+}
+
+impl Iterator for KeyScan<'_, '_> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if let Some(Err(err)) = self.bounded.take() {
+            self.done = true;
+            return Some(Err(err));
+        }
+        match self.cursor.next() {
+            // The key is packed "S", so it's the string and its nul.
+            Ok(()) => Some(
+                self.cursor
+                    .raw_cursor
+                    .get_packed_key_value()
+                    .map(|(mut key, _)| {
+                        key.pop();
+                        key
+                    }),
+            ),
+            Err(err) => {
+                self.done = true;
+                let reset = self.cursor.reset();
+                if err.is_not_found() {
+                    reset.err().map(Err)
+                } else {
+                    Some(Err(err))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for KeyScan<'_, '_> {
+    fn drop(&mut self) {
+        // A finished scan has reset the cursor already.
+        if !self.done {
+            let _ = self.cursor.reset();
+        }
+    }
+}
+
+impl<'a> Cursor<'a> {
+    /// Iterates over the keys of the object from `lower` (inclusive) to `upper`
+    /// (exclusive), e.g. to collect keys for an index or to delete them, without
+    /// decoding values. The key format must be `S`, and the keys are returned
+    /// without their nul. The scan bounds the cursor, and resets it (lifting
+    /// the bounds) once it's finished or dropped.
+    pub fn scan_keys_between(&self, lower: &str, upper: &str) -> KeyScan<'_, 'a> {
+        let bounded = if self.key_format() == "S" {
+            self.set_bounds(lower, upper)
+        } else {
+            Err(Error::with_kind(
+                ErrorKind::FormatMismatch,
+                format!(
+                    "format mismatch: can only scan \"S\" keys, not \"{}\"",
+                    self.key_format()
+                ),
+            ))
+        };
+        KeyScan {
+            cursor: self,
+            bounded: Some(bounded),
+            done: false,
+        }
+    }
+
+    /// Reads up to `n` rows starting at `start` (or the first key after it),
+    /// as `(key, value)` pairs like `get_raw_key_value`'s. Fewer are returned if
+    /// the object ends first. The cursor is left on the last row read.
+    pub fn read_from(&self, start: &str, n: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut rows = Vec::new();
+        if n == 0 {
+            return Ok(rows);
+        }
+        self.set_key(start)?;
+        let positioned = match self.search_near() {
+            Ok(CompareStatus::LessThan) => self.next(),
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        };
+        match positioned {
+            Ok(()) => {}
+            Err(err) if err.is_not_found() => return Ok(rows),
+            Err(err) => return Err(err),
+        }
+        loop {
+            let (key, value) = self.get_raw_key_value()?;
+            rows.push((key.unwrap_or_default(), value.unwrap_or_default()));
+            if rows.len() == n {
+                return Ok(rows);
+            }
+            match self.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => return Ok(rows),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn set_bounds(&self, lower: &str, upper: &str) -> Result<()> {
+        self.reset()?;
+        self.set_key(lower)?;
+        self.bound("action=set,bound=lower,inclusive=true")?;
+        self.set_key(upper)?;
+        self.bound("action=set,bound=upper,inclusive=false")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Connection, ErrorKind};
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_scan_keys_between() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:letters", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:letters"));
+        for c in 'a'..='z' {
+            assert_ok!(cur.set_key(&c.to_string()));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        }
+
+        let keys: Vec<Vec<u8>> = cur
+            .scan_keys_between("m", "p")
+            .map(|key| assert_ok!(key))
+            .collect();
+        assert_eq!(keys, [b"m", b"n", b"o"]);
+
+        // The bounds are gone afterwards, including after stopping early.
+        assert_ok!(cur.next());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).0, Some(b"a".to_vec()));
+        let first: Vec<Vec<u8>> = cur
+            .scan_keys_between("m", "p")
+            .take(2)
+            .map(|key| assert_ok!(key))
+            .collect();
+        assert_eq!(first, [b"m", b"n"]);
+        assert_ok!(cur.prev());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).0, Some(b"z".to_vec()));
+
+        // Only string keys can be bounded by strings.
+        assert_ok!(sess.create("table:numbers", "key_format=Q,value_format=S"));
+        let numbers = assert_ok!(sess.open_cursor("table:numbers"));
+        let mut scan = numbers.scan_keys_between("1", "2");
+        assert_eq!(
+            scan.next().unwrap().unwrap_err().kind,
+            ErrorKind::FormatMismatch
+        );
+        assert!(scan.next().is_none());
+    }
+
+    #[test]
+    fn test_read_from() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:rows", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:rows"));
+        assert!(assert_ok!(cur.read_from("a", 10)).is_empty());
+        for i in 0..50 {
+            assert_ok!(cur.set_key(&format!("{:02}", i * 2)));
+            assert_ok!(cur.set_value(&i.to_string()));
+            assert_ok!(cur.insert());
+        }
+        let keys = |rows: Vec<(Vec<u8>, Vec<u8>)>| -> Vec<String> {
+            rows.into_iter()
+                .map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect()
+        };
+
+        // Starting at a key that's present includes it.
+        let rows = assert_ok!(cur.read_from("40", 10));
+        assert_eq!(rows[0], (b"40".to_vec(), b"20".to_vec()));
+        assert_eq!(
+            keys(rows),
+            ["40", "42", "44", "46", "48", "50", "52", "54", "56", "58"]
+        );
+        // Starting between keys begins at the next one.
+        assert_eq!(keys(assert_ok!(cur.read_from("41", 2))), ["42", "44"]);
+        // Near the end there are fewer rows than asked for.
+        assert_eq!(keys(assert_ok!(cur.read_from("95", 10))), ["96", "98"]);
+        assert!(assert_ok!(cur.read_from("99", 10)).is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::raw_api::{CompareStatus, Result};
+use crate::{Cursor, Session};
+
+/// Where `Session::open_cursor_at` left the cursor relative to the key sought.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seek {
+    /// On the key itself.
+    Exact,
+    /// On the nearest key before it.
+    Before,
+    /// On the nearest key after it.
+    After,
+    /// Nowhere, because the object is empty.
+    Empty,
+}
+
+impl Session {
+    /// Opens a cursor on `uri` positioned on `key` or, if it's missing, a key
+    /// next to it, for one-off positioned reads.
+    pub fn open_cursor_at(&self, uri: &str, key: &str) -> Result<(Cursor<'_>, Seek)> {
+        let cursor = self.open_cursor(uri)?;
+        cursor.set_key(key)?;
+        let seek = match cursor.search_near() {
+            Ok(CompareStatus::Equal) => Seek::Exact,
+            Ok(CompareStatus::LessThan) => Seek::Before,
+            Ok(CompareStatus::GreaterThan) => Seek::After,
+            Err(err) if err.is_not_found() => Seek::Empty,
+            Err(err) => return Err(err),
+        };
+        Ok((cursor, seek))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Seek;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_open_cursor_at() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:seek", "key_format=S,value_format=S"));
+        let (_, seek) = assert_ok!(sess.open_cursor_at("table:seek", "b"));
+        assert_eq!(seek, Seek::Empty);
+
+        {
+            let cur = assert_ok!(sess.open_cursor("table:seek"));
+            for key in ["a", "c"] {
+                assert_ok!(cur.set_key(key));
+                assert_ok!(cur.set_value(key));
+                assert_ok!(cur.insert());
+            }
+        }
+
+        let (cur, seek) = assert_ok!(sess.open_cursor_at("table:seek", "c"));
+        assert_eq!(seek, Seek::Exact);
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).0, Some(b"c".to_vec()));
+
+        // Between keys, the cursor lands on one of the keys either side.
+        let (cur, seek) = assert_ok!(sess.open_cursor_at("table:seek", "b"));
+        let expected = match seek {
+            Seek::Before => b"a",
+            Seek::After => b"c",
+            _ => panic!("expected an inexact match, got {:?}", seek),
+        };
+        assert_eq!(
+            assert_ok!(cur.get_raw_key_value()).0,
+            Some(expected.to_vec())
+        );
+
+        let (cur, seek) = assert_ok!(sess.open_cursor_at("table:seek", "d"));
+        assert_eq!(seek, Seek::Before);
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).0, Some(b"c".to_vec()));
+    }
+}
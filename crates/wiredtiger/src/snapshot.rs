@@ -0,0 +1,128 @@
+use crate::raw_api::Result;
+use crate::{checkpoint_config, Connection, Cursor, Session};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_SNAPSHOT: AtomicU64 = AtomicU64::new(1);
+
+/// A read-only view of the database as of a checkpoint, from
+/// `Connection::snapshot`. It can be shared between threads, each opening its
+/// own session with `open_session`.
+pub struct Snapshot<'c> {
+    conn: &'c Connection,
+    checkpoint: String,
+    // Whether the checkpoint was taken for this snapshot, and so is dropped
+    // with it.
+    owned: bool,
+}
+
+/// A session whose cursors all read the snapshot's checkpoint.
+pub struct SnapshotSession<'s> {
+    session: Session,
+    checkpoint: &'s str,
+}
+
+impl Connection {
+    /// Opens a snapshot of the named checkpoint or, with `None`, of a new
+    /// checkpoint taken now, which is dropped along with the snapshot.
+    pub fn snapshot(&self, checkpoint: Option<&str>) -> Result<Snapshot<'_>> {
+        let (checkpoint, owned) = match checkpoint {
+            Some(name) => {
+                checkpoint_config(name)?;
+                (name.to_string(), false)
+            }
+            None => {
+                let name = format!(
+                    "rust_snapshot_{}",
+                    NEXT_SNAPSHOT.fetch_add(1, Ordering::Relaxed)
+                );
+                self.open_session()?.checkpoint(&format!("name={}", name))?;
+                (name, true)
+            }
+        };
+        Ok(Snapshot {
+            conn: self,
+            checkpoint,
+            owned,
+        })
+    }
+}
+
+impl<'c> Snapshot<'c> {
+    /// The name of the checkpoint the snapshot reads.
+    pub fn checkpoint(&self) -> &str {
+        &self.checkpoint
+    }
+
+    pub fn open_session(&self) -> Result<SnapshotSession<'_>> {
+        Ok(SnapshotSession {
+            session: self.conn.open_session()?,
+            checkpoint: &self.checkpoint,
+        })
+    }
+}
+
+impl<'c> Drop for Snapshot<'c> {
+    fn drop(&mut self) {
+        if self.owned {
+            // Failing to drop the checkpoint only leaves it behind, so it's not
+            // worth panicking over.
+            if let Ok(session) = self.conn.open_session() {
+                let _ = session.checkpoint(&format!("drop=({})", self.checkpoint));
+            }
+        }
+    }
+}
+
+impl<'s> SnapshotSession<'s> {
+    /// Opens a read-only cursor on `uri` as of the snapshot's checkpoint.
+    pub fn open_cursor(&self, uri: &str) -> Result<Cursor<'_>> {
+        self.session.open_checkpoint_cursor(uri, self.checkpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_snapshot_shared_between_threads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:shared", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:shared"));
+        let put = |k: &str| {
+            assert_ok!(cur.set_key(k));
+            assert_ok!(cur.set_value("v"));
+            assert_ok!(cur.insert());
+        };
+        put("a");
+        put("b");
+
+        let snapshot = assert_ok!(conn.snapshot(None));
+        // Later writes aren't part of the snapshot.
+        put("c");
+
+        let read = || {
+            let session = assert_ok!(snapshot.open_session());
+            let cursor = assert_ok!(session.open_cursor("table:shared"));
+            let mut keys = Vec::new();
+            while cursor.next().is_ok() {
+                let (key, _) = assert_ok!(cursor.get_raw_key_value());
+                keys.push(String::from_utf8(key.unwrap()).unwrap());
+            }
+            keys
+        };
+        let (first, second) = std::thread::scope(|scope| {
+            let first = scope.spawn(read);
+            let second = scope.spawn(read);
+            (first.join().unwrap(), second.join().unwrap())
+        });
+        assert_eq!(first, ["a", "b"]);
+        assert_eq!(second, first);
+    }
+}
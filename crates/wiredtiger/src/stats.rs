@@ -0,0 +1,262 @@
+use crate::raw_api::{c_string, Error, Result};
+use crate::{Connection, OpenConnectionConfig, Session, StatisticsOption};
+
+impl Session {
+    /// Reads the statistics for `uri` (e.g. `table:foo`, or an empty string for
+    /// the connection) as `(description, value)` pairs, such as
+    /// `("block-manager: file size in bytes", 4096)`. `config` is passed to the
+    /// statistics cursor, e.g. `statistics=(size)` to read only the on-disk size,
+    /// which works even when the connection doesn't keep statistics.
+    pub fn statistics(&self, uri: &str, config: &str) -> Result<Vec<(String, i64)>> {
+        let cursor = self.open_cursor_with_config(&format!("statistics:{}", uri), config)?;
+        let mut stats = Vec::new();
+        loop {
+            match cursor.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+            // Statistics values are packed as "SSq": the description, the value
+            // formatted for printing, and the value itself. The printable form is
+            // the easiest to decode.
+            let (_, value) = cursor.raw_cursor.get_packed_key_value()?;
+            let mut fields = value.split(|b| *b == 0);
+            let desc = String::from_utf8_lossy(fields.next().unwrap_or_default()).into_owned();
+            let printable = String::from_utf8_lossy(fields.next().unwrap_or_default());
+            let value = printable.parse().map_err(|_| {
+                Error::new(format!(
+                    "unexpected value for statistic {:?}: {:?}",
+                    desc, printable
+                ))
+            })?;
+            stats.push((desc, value));
+        }
+        Ok(stats)
+    }
+
+    /// The on-disk size of `uri` in bytes.
+    pub(crate) fn file_size(&self, uri: &str) -> Result<u64> {
+        self.statistics(uri, "statistics=(size)")?
+            .into_iter()
+            .find(|(desc, _)| desc == "block-manager: file size in bytes")
+            .map(|(_, size)| size as u64)
+            .ok_or_else(|| Error::new(format!("no file size statistic for {}", uri)))
+    }
+}
+
+// Quotes `s` as a JSON string.
+#[cfg(feature = "json")]
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Connection {
+    /// Opens the database at `path` keeping fast statistics, which are written
+    /// to `WiredTigerStat.*` files in it every `wait_secs` seconds, along with
+    /// those of the open objects matching `sources` (e.g. `table:`), for
+    /// profiling. A wait of zero would turn the logging off, so it's an error.
+    pub fn open_with_stats_log(path: &str, wait_secs: u16, sources: &[&str]) -> Result<Self> {
+        if wait_secs == 0 {
+            return Err(Error::new("statistics log interval must be at least 1s"));
+        }
+        let config = OpenConnectionConfig::builder()
+            .statistics(&[StatisticsOption::Fast])
+            .statistics_log(wait_secs, sources)
+            .build();
+        Self::open_with(path, &config)
+    }
+
+    /// Reads the connection's statistics as a JSON object mapping each
+    /// statistic's description to its value, e.g.
+    /// `{"cache: bytes currently in the cache":4096,...}`, for exporting to a
+    /// metrics system.
+    #[cfg(feature = "json")]
+    pub fn stats_json(&self) -> Result<String> {
+        let stats = self.open_session()?.statistics("", "")?;
+        let fields: Vec<String> = stats
+            .iter()
+            .map(|(desc, value)| format!("{}:{}", json_string(desc), value))
+            .collect();
+        Ok(format!("{{{}}}", fields.join(",")))
+    }
+
+    /// Free space on the filesystem holding the database, less the current size
+    /// of every table, whose files WiredTiger can need as much again to rewrite
+    /// (e.g. when compacting). For capacity alerts: a headroom of zero means the
+    /// database could fill the disk.
+    #[cfg(unix)]
+    pub fn disk_headroom(&self) -> Result<u64> {
+        let home = c_string(&self.get_home()?)?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(home.as_ptr(), &mut stat) } != 0 {
+            return Err(Error::new(format!(
+                "can't read filesystem usage of {:?}: {}",
+                home,
+                std::io::Error::last_os_error()
+            )));
+        }
+        let available = stat.f_bavail as u64 * stat.f_frsize as u64;
+
+        let session = self.open_session()?;
+        let mut tables_size = 0;
+        for (uri, _) in self.list_tables()? {
+            tables_size += session.file_size(&uri)?;
+        }
+        Ok(available.saturating_sub(tables_size))
+    }
+
+    /// Bytes written to disk, by the block manager and to the log, per byte of
+    /// keys and values the application has inserted or updated since the
+    /// connection was opened; a high ratio points at checkpoint, eviction or
+    /// compaction overhead. Needs statistics (e.g. `statistics=(fast)`), and
+    /// fails if nothing has been written yet.
+    pub fn write_amplification(&self) -> Result<f64> {
+        let stats = self.open_session()?.statistics("", "")?;
+        let sum = |names: &[&str]| -> i64 {
+            stats
+                .iter()
+                .filter(|(desc, _)| names.contains(&desc.as_str()))
+                .map(|(_, value)| value)
+                .sum()
+        };
+        let written = sum(&["block-manager: bytes written", "log: log bytes written"]);
+        let application = sum(&[
+            "cursor: cursor insert key and value bytes",
+            "cursor: cursor update key and value bytes",
+        ]);
+        if application <= 0 {
+            return Err(Error::new(
+                "no application writes to compute write amplification from",
+            ));
+        }
+        Ok(written as f64 / application as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_statistics() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create,statistics=(fast)"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:stats", "key_format=S,value_format=S"));
+
+        let stats = assert_ok!(sess.statistics("", ""));
+        assert!(stats.iter().any(|(desc, _)| desc.starts_with("cache: ")));
+        assert!(assert_ok!(sess.file_size("table:stats")) > 0);
+    }
+
+    #[test]
+    fn test_write_amplification() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create,statistics=(fast)"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:amplified", "key_format=S,value_format=S"));
+        assert!(conn.write_amplification().is_err());
+
+        // About 1MB of data, all of which a checkpoint writes out.
+        let cur = assert_ok!(sess.open_cursor("table:amplified"));
+        let value = "v".repeat(1000);
+        for i in 0..1000 {
+            assert_ok!(cur.set_key(&format!("{:04}", i)));
+            assert_ok!(cur.set_value(&value));
+            assert_ok!(cur.insert());
+        }
+        assert_ok!(sess.checkpoint(""));
+        let amplification = assert_ok!(conn.write_amplification());
+        assert!(amplification >= 1.0, "amplification {}", amplification);
+    }
+
+    #[test]
+    fn test_open_with_stats_log() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path().to_str().unwrap();
+        drop(assert_ok!(Connection::open(home, "create")));
+        assert!(Connection::open_with_stats_log(home, 0, &[]).is_err());
+
+        let conn = assert_ok!(Connection::open_with_stats_log(home, 1, &["table:"]));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:logged", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:logged"));
+        for i in 0..100 {
+            assert_ok!(cur.set_key(&i.to_string()));
+            assert_ok!(cur.set_value("value"));
+            assert_ok!(cur.insert());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(2500));
+
+        let logged = std::fs::read_dir(temp_dir.path()).unwrap().any(|entry| {
+            let name = entry.unwrap().file_name();
+            name.to_string_lossy().starts_with("WiredTigerStat.")
+        });
+        assert!(logged, "no statistics log file in {}", home);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_string() {
+        assert_eq!(super::json_string("a \"b\"\\\n"), r#""a \"b\"\\\u000a""#);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_stats_json() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create,statistics=(fast)"
+        ));
+        let json = assert_ok!(conn.stats_json());
+        let fields = json
+            .strip_prefix('{')
+            .and_then(|json| json.strip_suffix('}'))
+            .expect("expected a JSON object");
+        let key = "\"cache: maximum bytes configured\":";
+        let value = fields
+            .split(',')
+            .find_map(|field| field.strip_prefix(key))
+            .expect("expected the cache size statistic");
+        assert_eq!(value.parse::<i64>().unwrap(), 100 << 20);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_headroom() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = std::ffi::CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:headroom", "key_format=S,value_format=S"));
+
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::statvfs(home.as_ptr(), &mut stat) }, 0);
+        let capacity = stat.f_blocks as u64 * stat.f_frsize as u64;
+        let headroom = assert_ok!(conn.disk_headroom());
+        assert!(headroom > 0);
+        assert!(headroom < capacity);
+    }
+}
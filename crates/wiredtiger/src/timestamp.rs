@@ -0,0 +1,264 @@
+use crate::raw_api::{Error, Result};
+use crate::{Connection, Cursor, Session};
+use std::fmt;
+
+/// A WiredTiger timestamp. WiredTiger configuration strings carry timestamps
+/// in hexadecimal, which is how `Display` formats them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    /// Parses a hexadecimal timestamp, as returned by `query_timestamp`.
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        u64::from_str_radix(hex, 16)
+            .map(Timestamp)
+            .map_err(|_| Error::new(format!("invalid timestamp: {:?}", hex)))
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}", self.0)
+    }
+}
+
+// Version cursors (`debug=(dump_version=true)`) return every version of a record,
+// newest first, with the value prefixed by per-version metadata that includes
+// these timestamps.
+const START_TS: usize = 1;
+const STOP_TS: usize = 4;
+
+impl Session {
+    // Version cursors only work on file: URIs, so for a table find the file that
+    // holds it. Only tables stored in a single column group have one.
+    pub(crate) fn backing_file(&self, uri: &str) -> Result<String> {
+        let Some(table) = uri.strip_prefix("table:") else {
+            return Ok(uri.to_string());
+        };
+        let metadata = self.open_cursor("metadata:")?;
+        metadata.set_key(&format!("colgroup:{}", table))?;
+        metadata.search().map_err(|err| {
+            if err.is_not_found() {
+                Error::new(format!("{} isn't stored in a single file", uri))
+            } else {
+                err
+            }
+        })?;
+        let (_, config) = metadata.get_raw_key_value()?;
+        let config = String::from_utf8_lossy(&config.unwrap_or_default()).into_owned();
+        let source = config
+            .split_once("source=")
+            .map(|(_, rest)| rest.trim_start_matches('"'))
+            .and_then(|rest| rest.split(['"', ',']).next())
+            .ok_or_else(|| Error::new(format!("no source file in metadata for {}", uri)))?;
+        Ok(source.to_string())
+    }
+}
+
+// Where WiredTiger keeps the older versions of timestamped updates.
+const HISTORY_STORE: &str = "file:WiredTigerHS.wt";
+
+impl Connection {
+    /// Whether the connection supports timestamps, going by whether it answers a
+    /// timestamp query, so callers can fall back when it doesn't.
+    pub fn timestamps_enabled(&self) -> bool {
+        self.query_timestamp("get=all_durable").is_ok()
+    }
+
+    /// Discards the history store's old versions by moving the oldest and stable
+    /// timestamps up to the newest durable commit and checkpointing, returning
+    /// how many bytes the history store file shrank by. Afterwards nothing can
+    /// be read as of an earlier timestamp, and a crash or `rollback_to_stable`
+    /// can no longer go back past it.
+    pub fn clear_history_store(&self) -> Result<u64> {
+        let session = self.open_session()?;
+        let before = session.file_size(HISTORY_STORE)?;
+        let newest = self.query_timestamp("get=all_durable")?;
+        if newest.0 != 0 {
+            self.set_timestamp(&format!(
+                "oldest_timestamp={0},stable_timestamp={0}",
+                newest
+            ))?;
+        }
+        session.checkpoint("")?;
+        Ok(before.saturating_sub(session.file_size(HISTORY_STORE)?))
+    }
+}
+
+impl<'a> Cursor<'a> {
+    /// Returns the current value (packed, as WiredTiger stores it) together with
+    /// the commit timestamp of the version that was read, or `None` if it was
+    /// written without one. The cursor's table must be stored in a single file.
+    pub fn get_value_with_timestamp(&self) -> Result<(Vec<u8>, Option<u64>)> {
+        let (key, value) = self.raw_cursor.get_packed_key_value()?;
+        // 0 if the session isn't reading as of a timestamp.
+        let read_ts = self.session.query_timestamp("get=read")?.0;
+        let file = self.session.backing_file(&self.raw_cursor.uri())?;
+
+        // A version cursor can't be opened inside a transaction, which the caller
+        // may well be in, so look the versions up from a session of our own.
+        let session = self.session.conn.open_session()?;
+        let versions = session.open_cursor_with_config(&file, "raw,debug=(dump_version=true)")?;
+        let metadata_format = versions
+            .raw_cursor
+            .value_format()
+            .strip_suffix(self.raw_cursor.value_format())
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "can't read versions of {}: its value format doesn't match the cursor's",
+                    file
+                ))
+            })?
+            .to_string();
+
+        versions.raw_cursor.set_key_item(&key)?;
+        versions.search()?;
+        loop {
+            let (version_key, version) = versions.raw_cursor.get_packed_key_value()?;
+            if version_key != key {
+                break;
+            }
+            // The version that was read is the newest one with a matching value
+            // that was visible at the read timestamp, if there is one.
+            let (metadata, used) = session
+                .raw_session
+                .unpack_uints(&metadata_format, &version)?;
+            let visible =
+                read_ts == 0 || (metadata[START_TS] <= read_ts && read_ts < metadata[STOP_TS]);
+            if visible && version[used..] == value[..] {
+                let ts = metadata[START_TS];
+                return Ok((value, (ts != 0).then_some(ts)));
+            }
+            match versions.next() {
+                Ok(()) => {}
+                Err(err) if err.is_not_found() => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((value, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+    use crate::Connection;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_timestamp_hex() {
+        assert_eq!(Timestamp(0x2a).to_string(), "2a");
+        assert_eq!(assert_ok!(Timestamp::from_hex("2a")), Timestamp(42));
+        assert!(Timestamp::from_hex("xyz").is_err());
+    }
+
+    #[test]
+    fn test_get_value_with_timestamp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:versioned", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:versioned"));
+        for (value, ts) in [("v1", "2a"), ("v2", "40")] {
+            assert_ok!(sess.begin_transaction(""));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value(value));
+            assert_ok!(cur.insert());
+            assert_ok!(sess.commit_transaction(&format!("commit_timestamp={}", ts)));
+        }
+
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_value_with_timestamp()),
+            (b"v2\0".to_vec(), Some(0x40))
+        );
+
+        // Reading as of an earlier timestamp reports the older version.
+        assert_ok!(sess.begin_transaction("read_timestamp=30"));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_value_with_timestamp()),
+            (b"v1\0".to_vec(), Some(0x2a))
+        );
+        assert_ok!(sess.rollback_transaction(""));
+    }
+
+    #[test]
+    fn test_get_value_with_timestamp_repeated_value() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:repeated", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:repeated"));
+        // The same value committed twice, at 10 and at 20.
+        for ts in [10, 20] {
+            assert_ok!(sess.begin_transaction(""));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value("same"));
+            assert_ok!(cur.insert());
+            assert_ok!(sess.commit_transaction(&format!("commit_timestamp={:x}", ts)));
+        }
+
+        // Reading at 15 sees the version committed at 10.
+        assert_ok!(sess.begin_transaction(&format!("read_timestamp={:x}", 15)));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(
+            assert_ok!(cur.get_value_with_timestamp()),
+            (b"same\0".to_vec(), Some(10))
+        );
+        assert_ok!(sess.rollback_transaction(""));
+    }
+
+    #[test]
+    fn test_timestamps_enabled() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        assert!(conn.timestamps_enabled());
+    }
+
+    #[test]
+    fn test_clear_history_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:history", "key_format=S,value_format=S"));
+        assert_ok!(conn.set_timestamp("oldest_timestamp=1"));
+        let cur = assert_ok!(sess.open_cursor("table:history"));
+        let value = "v".repeat(1000);
+        for ts in 2..200 {
+            let mut txn = assert_ok!(sess.transaction(""));
+            for i in 0..20 {
+                assert_ok!(cur.set_key(&format!("{:02}", i)));
+                assert_ok!(cur.set_value(&format!("{}{}", value, ts)));
+                assert_ok!(cur.insert());
+            }
+            assert_ok!(txn.commit_with_timestamp(Timestamp(ts)));
+        }
+        // Checkpointing at a stable timestamp below the newest commits writes
+        // the older versions to the history store.
+        assert_ok!(conn.set_timestamp(&format!("stable_timestamp={}", Timestamp(199))));
+        assert_ok!(sess.checkpoint(""));
+        drop(cur);
+
+        assert!(assert_ok!(conn.clear_history_store()) > 0);
+        assert_eq!(
+            assert_ok!(conn.query_timestamp("get=oldest_timestamp")),
+            Timestamp(199)
+        );
+    }
+}
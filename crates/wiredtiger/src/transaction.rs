@@ -0,0 +1,518 @@
+use crate::raw_api::{Error, Result};
+use crate::{Cursor, Session, Timestamp};
+use std::time::Duration;
+use wiredtiger_sys as wtffi;
+
+/// How long to wait before retrying a rolled back transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// Retry straight away.
+    None,
+    /// Wait the same time before every retry.
+    Fixed(Duration),
+    /// Wait `base` before the first retry, doubling each time up to `max`.
+    Exponential { base: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// The wait before retry number `retry`, counting from zero.
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max } => base
+                .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX))
+                .min(max),
+        }
+    }
+}
+
+/// How `Session::with_transaction_policy` retries transactions that fail with
+/// `WT_ROLLBACK`, and how `Connection::open_resilient` retries opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The most attempts to make, including the first; at least one.
+    pub max_attempts: usize,
+    pub backoff: Backoff,
+}
+
+/// Counts of the transactions run by `Session::with_transaction`, from
+/// `Session::conflict_stats`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConflictStats {
+    /// Transactions begun.
+    pub attempts: u64,
+    /// Transactions that committed.
+    pub commits: u64,
+    /// Transactions that were rolled back, because the closure failed (e.g. on
+    /// a write conflict) or the commit did.
+    pub rollbacks: u64,
+}
+
+/// A transaction on a session. It's rolled back when dropped unless it has
+/// been committed or rolled back already.
+pub struct Transaction<'s> {
+    session: &'s Session,
+    finished: bool,
+    // Whether it was begun by `with_transaction`, whose outcome is counted in
+    // `conflict_stats` even when a panic leaves the rollback to `Drop`.
+    counted: bool,
+}
+
+impl Session {
+    /// Begins a transaction. `config` is passed to `begin_transaction`.
+    pub fn transaction(&self, config: &str) -> Result<Transaction<'_>> {
+        self.begin_transaction(config)?;
+        Ok(Transaction {
+            session: self,
+            finished: false,
+            counted: false,
+        })
+    }
+}
+
+impl Session {
+    /// Runs `f` in a transaction, committing it if `f` succeeds and rolling it
+    /// back if `f` fails or panics. `config` is passed to `begin_transaction`.
+    /// The outcome is counted in `conflict_stats`.
+    pub fn with_transaction<T, F: FnOnce(&Session) -> Result<T>>(
+        &self,
+        config: &str,
+        f: F,
+    ) -> Result<T> {
+        let mut txn = self.transaction(config)?;
+        txn.counted = true;
+        self.count(|stats| stats.attempts += 1);
+        // If `f` panics, `txn` rolls the transaction back as it's dropped.
+        let result = f(self).and_then(|value| txn.commit().map(|()| value));
+        match &result {
+            Ok(_) => self.count(|stats| stats.commits += 1),
+            Err(_) => {
+                // A failed commit has already rolled the transaction back.
+                if !txn.finished {
+                    txn.rollback()?;
+                }
+                self.count(|stats| stats.rollbacks += 1);
+            }
+        }
+        result
+    }
+
+    /// Like `with_transaction`, but when the transaction fails with
+    /// `WT_ROLLBACK` (e.g. on a write conflict) `f` is run again in a new one,
+    /// up to `max_retries` times, sleeping twice as long before each retry. The
+    /// last error is returned once the retries run out.
+    pub fn transaction_with_retry<T, F: FnMut(&Session) -> Result<T>>(
+        &self,
+        max_retries: usize,
+        f: F,
+    ) -> Result<T> {
+        let policy = RetryPolicy {
+            max_attempts: max_retries.saturating_add(1),
+            backoff: Backoff::Exponential {
+                base: Duration::from_millis(1),
+                max: Duration::from_millis(100),
+            },
+        };
+        self.with_transaction_policy(&policy, f)
+    }
+
+    /// Like `with_transaction`, but when the transaction fails with
+    /// `WT_ROLLBACK` `f` is run again in a new one, as set out by `policy`. The
+    /// last error is returned once the attempts run out.
+    pub fn with_transaction_policy<T, F: FnMut(&Session) -> Result<T>>(
+        &self,
+        policy: &RetryPolicy,
+        f: F,
+    ) -> Result<T> {
+        self.retry_with_sleep(policy, std::thread::sleep, f)
+    }
+
+    // Sleeps with `sleep`, so tests can see the waits without waiting.
+    fn retry_with_sleep<T, S: FnMut(Duration), F: FnMut(&Session) -> Result<T>>(
+        &self,
+        policy: &RetryPolicy,
+        mut sleep: S,
+        mut f: F,
+    ) -> Result<T> {
+        let mut retry = 0;
+        loop {
+            match self.with_transaction("", &mut f) {
+                Err(err)
+                    if err.code == wtffi::WT_ROLLBACK
+                        && (retry as usize) + 1 < policy.max_attempts =>
+                {
+                    sleep(policy.backoff.delay(retry));
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// How many of this session's `with_transaction` calls committed or rolled
+    /// back, for tuning retry logic under contention.
+    pub fn conflict_stats(&self) -> ConflictStats {
+        self.conflict_stats.get()
+    }
+
+    fn count<F: FnOnce(&mut ConflictStats)>(&self, f: F) {
+        let mut stats = self.conflict_stats.get();
+        f(&mut stats);
+        self.conflict_stats.set(stats);
+    }
+}
+
+impl<'s> Transaction<'s> {
+    fn finish<F: FnOnce(&Session) -> Result<()>>(&mut self, f: F) -> Result<()> {
+        if self.finished {
+            return Err(Error::new("transaction has already finished"));
+        }
+        // WiredTiger ends the transaction even if the commit fails.
+        self.finished = true;
+        f(self.session)
+    }
+
+    pub fn commit(&mut self) -> Result<()> {
+        self.finish(|session| session.commit_transaction(""))
+    }
+
+    /// Commits the transaction's updates at `commit_ts`.
+    pub fn commit_with_timestamp(&mut self, commit_ts: Timestamp) -> Result<()> {
+        self.finish(|session| {
+            session.commit_transaction(&format!("commit_timestamp={}", commit_ts))
+        })
+    }
+
+    pub fn rollback(&mut self) -> Result<()> {
+        self.finish(|session| session.rollback_transaction(""))
+    }
+
+    /// Opens a cursor on `uri` that reads in this transaction, i.e. as of its
+    /// read timestamp if it has one. The cursor borrows the transaction, so it
+    /// has to be dropped before the transaction is committed or rolled back.
+    /// `config` is passed to `open_cursor`.
+    pub fn open_cursor(&self, uri: &str, config: &str) -> Result<Cursor<'_>> {
+        if self.finished {
+            return Err(Error::new("transaction has already finished"));
+        }
+        self.session.open_cursor_with_config(uri, config)
+    }
+}
+
+impl<'s> Drop for Transaction<'s> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let result = self.session.rollback_transaction("");
+            if self.counted {
+                self.session.count(|stats| stats.rollbacks += 1);
+            }
+            // Panicking again while unwinding would abort.
+            if !std::thread::panicking() {
+                result.unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, RetryPolicy};
+    use crate::raw_api::Result;
+    use crate::{ConflictStats, Connection, Error, Timestamp};
+    use assert_ok::assert_ok;
+    use std::panic::AssertUnwindSafe;
+    use std::time::Duration;
+    use wiredtiger_sys as wtffi;
+
+    #[test]
+    fn test_conflict_stats() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let rival = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:contended", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:contended"));
+        let rival_cur = assert_ok!(rival.open_cursor("table:contended"));
+
+        for i in 0..5 {
+            let value = i.to_string();
+            // Each write is attempted once while the rival holds an uncommitted
+            // write to the same key, and retried after the rival commits.
+            let mut rival_txn = assert_ok!(rival.transaction(""));
+            assert_ok!(rival_cur.set_key("k"));
+            assert_ok!(rival_cur.set_value("rival"));
+            assert_ok!(rival_cur.insert());
+            let write = |_: &crate::Session| {
+                cur.set_key("k")?;
+                cur.set_value(&value)?;
+                cur.insert()
+            };
+            let err = sess.with_transaction("", write).unwrap_err();
+            assert_eq!(err.code, wtffi::WT_ROLLBACK);
+            assert_ok!(rival_txn.commit());
+            assert_ok!(sess.with_transaction("", write));
+        }
+
+        assert_eq!(
+            sess.conflict_stats(),
+            ConflictStats {
+                attempts: 10,
+                commits: 5,
+                rollbacks: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let ms = Duration::from_millis;
+        assert_eq!(Backoff::None.delay(3), Duration::ZERO);
+        assert_eq!(Backoff::Fixed(ms(5)).delay(3), ms(5));
+        let exponential = Backoff::Exponential {
+            base: ms(1),
+            max: ms(10),
+        };
+        let delays: Vec<_> = (0..6).map(|retry| exponential.delay(retry)).collect();
+        assert_eq!(delays, [ms(1), ms(2), ms(4), ms(8), ms(10), ms(10)]);
+        assert_eq!(exponential.delay(100), ms(10));
+    }
+
+    #[test]
+    fn test_with_transaction_policy() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            backoff: Backoff::Exponential {
+                base: Duration::from_millis(10),
+                max: Duration::from_secs(1),
+            },
+        };
+        let conflict = || Error {
+            code: wtffi::WT_ROLLBACK,
+            ..Error::new("conflict")
+        };
+
+        // Every attempt conflicts, so the policy sleeps longer each time before
+        // giving up.
+        let mut sleeps = Vec::new();
+        let mut attempts = 0;
+        let err = sess
+            .retry_with_sleep(
+                &policy,
+                |delay| sleeps.push(delay),
+                |_| -> Result<()> {
+                    attempts += 1;
+                    Err(conflict())
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err.code, wtffi::WT_ROLLBACK);
+        assert_eq!(attempts, 4);
+        assert_eq!(sleeps, [10, 20, 40].map(Duration::from_millis));
+
+        // Other errors aren't retried.
+        let mut attempts = 0;
+        assert!(sess
+            .with_transaction_policy(&policy, |_| -> Result<()> {
+                attempts += 1;
+                Err(Error::new("give up"))
+            })
+            .is_err());
+        assert_eq!(attempts, 1);
+
+        let mut attempts = 0;
+        let value = assert_ok!(sess.retry_with_sleep(
+            &policy,
+            |_| {},
+            |_| {
+                attempts += 1;
+                if attempts < 3 {
+                    Err(conflict())
+                } else {
+                    Ok(attempts)
+                }
+            }
+        ));
+        assert_eq!(value, 3);
+    }
+
+    #[test]
+    fn test_transaction_with_retry() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:counter", "key_format=S,value_format=S"));
+
+        // Both threads increment the same counter, so their transactions
+        // conflict and have to be retried.
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                let conn = conn.clone();
+                scope.spawn(move || {
+                    let sess = assert_ok!(conn.open_session());
+                    let cur = assert_ok!(sess.open_cursor("table:counter"));
+                    for _ in 0..50 {
+                        assert_ok!(sess.transaction_with_retry(usize::MAX, |_| {
+                            cur.set_key("n")?;
+                            let n = match cur.search() {
+                                Ok(()) => {
+                                    let (_, value) = cur.get_raw_key_value()?;
+                                    String::from_utf8(value.unwrap()).unwrap().parse().unwrap()
+                                }
+                                Err(err) if err.is_not_found() => 0,
+                                Err(err) => return Err(err),
+                            };
+                            cur.set_key("n")?;
+                            cur.set_value(&(n + 1u64).to_string())?;
+                            cur.insert()
+                        }));
+                    }
+                });
+            }
+        });
+
+        let cur = assert_ok!(sess.open_cursor("table:counter"));
+        assert_ok!(cur.set_key("n"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).1, Some(b"100".to_vec()));
+    }
+
+    #[test]
+    fn test_with_transaction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:txn", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:txn"));
+        let insert = |key: &str| {
+            cur.set_key(key)?;
+            cur.set_value("v")?;
+            cur.insert()
+        };
+
+        assert_eq!(
+            assert_ok!(sess.with_transaction("", |_| insert("committed").map(|_| 1))),
+            1
+        );
+        let err = sess
+            .with_transaction("", |_| {
+                insert("failed")?;
+                Err::<(), _>(Error::new("give up"))
+            })
+            .unwrap_err();
+        assert_eq!(err.message, "give up");
+        let panicked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            sess.with_transaction("", |_| -> Result<()> {
+                insert("panicked")?;
+                panic!("give up");
+            })
+        }));
+        assert!(panicked.is_err());
+
+        assert_ok!(cur.set_key("committed"));
+        assert_ok!(cur.search());
+        for key in ["failed", "panicked"] {
+            assert_ok!(cur.set_key(key));
+            assert!(cur.search().unwrap_err().is_not_found());
+        }
+        assert_eq!(
+            sess.conflict_stats(),
+            ConflictStats {
+                attempts: 3,
+                commits: 1,
+                rollbacks: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_commit_with_timestamp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:ts", "key_format=S,value_format=S"));
+        let cur = assert_ok!(sess.open_cursor("table:ts"));
+        for (value, ts) in [("old", 10), ("new", 20)] {
+            let mut txn = assert_ok!(sess.transaction(""));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value(value));
+            assert_ok!(cur.insert());
+            assert_ok!(txn.commit_with_timestamp(Timestamp(ts)));
+            assert!(txn.commit().is_err());
+        }
+
+        // Reading as of a timestamp before the second commit doesn't see it.
+        let mut txn = assert_ok!(sess.transaction(""));
+        assert_ok!(sess.timestamp_transaction(&format!("read_timestamp={}", Timestamp(15))));
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).1, Some(b"old".to_vec()));
+        assert_ok!(txn.rollback());
+
+        assert_ok!(cur.set_key("k"));
+        assert_ok!(cur.search());
+        assert_eq!(assert_ok!(cur.get_raw_key_value()).1, Some(b"new".to_vec()));
+    }
+
+    #[test]
+    fn test_transaction_open_cursor() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let conn = assert_ok!(Connection::open(
+            temp_dir.path().to_str().unwrap(),
+            "create"
+        ));
+        let sess = assert_ok!(conn.open_session());
+        assert_ok!(sess.create("table:as_of", "key_format=S,value_format=S"));
+        let writer = assert_ok!(conn.open_session());
+        let cur = assert_ok!(writer.open_cursor("table:as_of"));
+        let write = |value: &str, ts| {
+            let mut txn = assert_ok!(writer.transaction(""));
+            assert_ok!(cur.set_key("k"));
+            assert_ok!(cur.set_value(value));
+            assert_ok!(cur.insert());
+            assert_ok!(txn.commit_with_timestamp(Timestamp(ts)));
+        };
+        write("old", 10);
+        write("new", 20);
+
+        let mut txn = assert_ok!(sess.transaction(&format!("read_timestamp={}", Timestamp(15))));
+        {
+            let reader = assert_ok!(txn.open_cursor("table:as_of", ""));
+            assert_ok!(reader.set_key("k"));
+            assert_ok!(reader.search());
+            assert_eq!(
+                assert_ok!(reader.get_raw_key_value()).1,
+                Some(b"old".to_vec())
+            );
+
+            // Later commits aren't seen either.
+            write("newer", 30);
+            assert_ok!(reader.set_key("k"));
+            assert_ok!(reader.search());
+            assert_eq!(
+                assert_ok!(reader.get_raw_key_value()).1,
+                Some(b"old".to_vec())
+            );
+        }
+        assert_ok!(txn.commit());
+        assert!(txn.open_cursor("table:as_of", "").is_err());
+    }
+}
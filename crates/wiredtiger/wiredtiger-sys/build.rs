@@ -1,29 +1,102 @@
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+// Whether the `dynamic` feature asks for the shared library rather than the
+// static one.
+fn dynamic() -> bool {
+    env::var_os("CARGO_FEATURE_DYNAMIC").is_some()
+}
+
+fn link_wt(lib_dir: &Path) {
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    // Statically linking requires that WT was configured with the
+    // -DENABLE_STATIC=1 option to cmake.
+    let kind = if dynamic() { "dylib" } else { "static" };
+    println!("cargo:rustc-link-lib={}=wiredtiger", kind);
+    // So that binaries, the tests included, find the shared library at run time.
+    if dynamic() {
+        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir.display());
+    }
+}
+
+// Runs `git` in the WiredTiger source, returning its trimmed output.
+fn git_wt(args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir("wiredtiger")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Identifies the WiredTiger source: its commit or, outside a git checkout, its
+// release info, so that bumping the version forces a rebuild.
+fn wt_version() -> String {
+    git_wt(&["rev-parse", "HEAD"])
+        .unwrap_or_else(|| fs::read_to_string("wiredtiger/RELEASE_INFO").unwrap_or_default())
+}
+
 fn build_wt() -> std::io::Result<()> {
     let wt_dir = "wiredtiger";
     let build_dir = format!("{wt_dir}/build");
+
+    // Building WiredTiger takes minutes, so skip it if the library was already
+    // built from this version, and linked the same way.
+    let library = if dynamic() {
+        "libwiredtiger.so"
+    } else {
+        "libwiredtiger.a"
+    };
+    let stamp = Path::new(&build_dir).join("rust-build-stamp");
+    let version = format!(
+        "{}\n{}",
+        wt_version(),
+        if dynamic() { "dynamic" } else { "static" }
+    );
+    if Path::new(&build_dir).join(library).exists()
+        && fs::read_to_string(&stamp).is_ok_and(|built| built == version)
+    {
+        return Ok(());
+    }
+
+    // Always say which, since cmake otherwise keeps the choice cached from the
+    // previous build.
+    let enable_static = if dynamic() { 0 } else { 1 };
     Command::new("cmake")
-        .arg("-DENABLE_STATIC=1")
+        .arg(format!("-DENABLE_STATIC={}", enable_static))
         .arg("-S")
         .arg(wt_dir)
         .arg("-B")
         .arg(&build_dir)
         .output()?;
-    Command::new("cmake")
+    let build = Command::new("cmake")
         .arg("--build")
         .arg(&build_dir)
         .arg("-j16")
         .output()?;
+    if build.status.success() {
+        fs::write(stamp, version)?;
+    }
     Ok(())
 }
 
-fn bindgen_wt() {
+fn bindgen_wt(header: &Path) {
     let bindings = bindgen::Builder::default()
         // The input header we would like to generate bindings for.
-        .header("wiredtiger/build/include/wiredtiger.h")
+        .header(header.to_str().expect("header path isn't valid UTF-8"))
+        // Only generate WiredTiger's own API, and whatever it refers to, rather
+        // than everything the system headers it includes declare.
+        .allowlist_type("WT_.*|__wt_.*")
+        .allowlist_function("wiredtiger_.*")
+        .allowlist_var("WT_.*")
+        // Any of libc's stdio internals that are still pulled in are left
+        // opaque rather than duplicating their definitions.
+        .opaque_type("_IO_.*")
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -38,20 +111,35 @@ fn bindgen_wt() {
 }
 
 fn main() {
+    println!("cargo:rerun-if-env-changed=WIREDTIGER_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=WIREDTIGER_INCLUDE_DIR");
+
+    // Link against an installed WiredTiger if we're told where it is, rather
+    // than building the vendored one.
+    if let Some(lib_dir) = env::var_os("WIREDTIGER_LIB_DIR") {
+        let lib_dir = PathBuf::from(lib_dir);
+        let include_dir = env::var_os("WIREDTIGER_INCLUDE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| lib_dir.join("../include"));
+        bindgen_wt(&include_dir.join("wiredtiger.h"));
+        link_wt(&lib_dir);
+        return;
+    }
+
     if !Path::new("wiredtiger/LICENSE").exists() {
         update_submodules();
     }
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=wiredtiger/RELEASE_INFO");
+    // Moving the submodule to another commit changes its HEAD.
+    if let Some(git_dir) = git_wt(&["rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+    }
     build_wt().expect("Failed to build wiredtiger");
 
-    bindgen_wt();
-
-    // Tell cargo to look for shared libraries in the specified directory.
-    // Note that this search path is relative to the repo root.
-    println!("cargo:rustc-link-search=crates/wiredtiger/wiredtiger-sys/wiredtiger/build");
-
-    // Tell cargo to tell rustc to statically link with the wiredtiger library.
-    // This requires that WT was configured with the -DENABLE_STATIC=1 option to cmake.
-    println!("cargo:rustc-link-lib=static=wiredtiger");
+    bindgen_wt(Path::new("wiredtiger/build/include/wiredtiger.h"));
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    link_wt(&manifest_dir.join("wiredtiger/build"));
 }
 
 fn update_submodules() {